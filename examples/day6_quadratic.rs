@@ -0,0 +1,68 @@
+// Day 6 without the brute-force loop.
+// The boat wins when c * (T - c) > D, i.e. for every integer c strictly between
+// the roots of c^2 - T*c + D = 0, so we solve the quadratic and count.
+
+use aoc::{Aoc, AocRes};
+use regex::Regex;
+use std::fs;
+
+pub struct Day6Quadratic {
+    times: Vec<String>,
+    distances: Vec<String>,
+}
+
+impl Aoc for Day6Quadratic {
+    fn new(path_to_input: &str) -> Self {
+        let contents = fs::read_to_string(path_to_input).unwrap();
+        let re = Regex::new(r"\d+").unwrap();
+        let mut lines = contents.lines();
+        let numbers = |line: Option<&str>| -> Vec<String> {
+            re.find_iter(line.unwrap_or(""))
+                .map(|m| m.as_str().to_string())
+                .collect()
+        };
+        let times = numbers(lines.next());
+        let distances = numbers(lines.next());
+        Self { times, distances }
+    }
+
+    fn part1(&self) -> AocRes {
+        let answer = std::iter::zip(&self.times, &self.distances).fold(1, |acc, (t, d)| {
+            acc * Self::winning_combinations(t.parse().unwrap(), d.parse().unwrap())
+        });
+        AocRes::Int32(answer as i32)
+    }
+
+    fn part2(&self) -> AocRes {
+        let total_time = self.times.concat().parse().unwrap();
+        let distance_to_beat = self.distances.concat().parse().unwrap();
+        AocRes::Int32(Self::winning_combinations(total_time, distance_to_beat) as i32)
+    }
+}
+
+impl Day6Quadratic {
+    fn winning_combinations(total_time: i64, distance_to_beat: i64) -> i64 {
+        let wins = |c: i64| c * (total_time - c) > distance_to_beat;
+        let discriminant = (total_time * total_time - 4 * distance_to_beat) as f64;
+        if discriminant < 0.0 {
+            return 0;
+        }
+        let root = discriminant.sqrt();
+        let mut low = ((total_time as f64 - root) / 2.0).floor() as i64;
+        let mut high = ((total_time as f64 + root) / 2.0).ceil() as i64;
+        // Floating point can land on either side of the exact root, so nudge inwards
+        while !wins(low) && low < high {
+            low += 1;
+        }
+        while !wins(high) && high > low {
+            high -= 1;
+        }
+        if wins(low) {
+            high - low + 1
+        } else {
+            0
+        }
+    }
+}
+
+aoc::export_aoc_plugin!(6, "Wait For It (quadratic formula)", Day6Quadratic);
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Overflows are reported as errors rather than crashes, as with `aoc fuzz`
+fuzz_target!(init: aoc::numeric::set_checked(true), |data: &[u8]| {
+    aoc::fuzz::fuzz_day(3, data)
+});
@@ -1,5 +1,12 @@
+use crate::explain::Explanation;
+use crate::image::CellImage;
+use crate::variants::Variant;
+use std::error::Error;
 use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum AocRes {
     Int32(i32),
     Int64(i64),
@@ -18,11 +25,73 @@ impl fmt::Display for AocRes {
     }
 }
 
+#[derive(Debug)]
+pub enum AocError {
+    Plugin { path: PathBuf, reason: String },
+    Overflow { day: u32, part: u32, operation: String },
+    Timeout { day: u32, part: u32, after: Duration },
+    Worker { day: u32, part: u32, message: String },
+    Config { source: String, reason: String },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Plugin { path, reason } => {
+                write!(f, "plugin {}: {}", path.display(), reason)
+            }
+            AocError::Overflow { day, part, operation } => {
+                write!(f, "day{} part{}: {} overflows", day, part, operation)
+            }
+            AocError::Worker { day, part, message } => {
+                write!(f, "day{} part{}: a worker panicked: {}", day, part, message)
+            }
+            AocError::Timeout { day, part, after } => {
+                write!(f, "day{} part{}: gave up after {:?}", day, part, after)
+            }
+            AocError::Config { source, reason } => write!(f, "{}: {}", source, reason),
+        }
+    }
+}
+
+impl Error for AocError {}
+
 pub trait Aoc
 {
-    fn new<'a>(path_to_input: &'a String) -> Self where Self: Sized;
+    fn new(path_to_input: &str) -> Self where Self: Sized;
     fn part1(&self) -> AocRes;
     fn part2(&self) -> AocRes;
+
+    // Part of the result cache key, bump it whenever a change could alter the answers
+    fn version(&self) -> &str {
+        "1"
+    }
+
+    // Reads the input into the model the parts work on, for the fuzz targets. A
+    // malformed input must come back as an error, never as a panic
+    fn parse(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // How each input record contributed to the answer, for days that can tell
+    fn explain(&self, _part: u32) -> Option<Explanation> {
+        None
+    }
+
+    // The input grid colored by what each cell means to the solution, for grid days
+    fn image(&self) -> Option<CellImage> {
+        None
+    }
+
+    // Named alternative implementations of a part, the one part1/part2 use first
+    fn variants(&self, _part: u32) -> Vec<Variant<'_>> {
+        Vec::new()
+    }
+
+    // The model the parts work on, as text for the snapshot tests in tests/snapshots
+    fn snapshot(&self) -> Option<String> {
+        None
+    }
 }
 
 // If a trait takes in generic type parameters, then it cannot be made into a trait object
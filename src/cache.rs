@@ -0,0 +1,151 @@
+//! On-disk cache of answers, keyed by day, part, input contents and solver version.
+//! Each entry is a small text file named after its key, e.g.
+//! `.aoc-cache/day6-part2-1-3c9a41e0b5f2d7a8` holding `Int32 29891250`.
+//! Answers from runs with checked arithmetic are kept apart, with a `-checked`
+//! suffix, so an overflow is never hidden behind an answer that wrapped.
+
+use crate::aoc::AocRes;
+use crate::numeric;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CACHE_DIR: &str = "./.aoc-cache";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub day: u32,
+    pub part: u32,
+    pub version: String,
+    pub input_hash: u64,
+    pub checked: bool,
+}
+
+#[derive(Clone)]
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+// 64-bit FNV-1a: stable across builds and platforms, unlike std's DefaultHasher
+pub fn input_hash(contents: &[u8]) -> u64 {
+    contents.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl CacheKey {
+    pub fn new(day: u32, part: u32, version: &str, input: &[u8]) -> Self {
+        Self {
+            day,
+            part,
+            version: version.to_string(),
+            input_hash: input_hash(input),
+            checked: numeric::is_checked(),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        // Versions are free-form, keep only characters that are safe in a file name
+        let version: String = self
+            .version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' })
+            .collect();
+        format!(
+            "day{}-part{}-{}-{:016x}{}",
+            self.day,
+            self.part,
+            version,
+            self.input_hash,
+            if self.checked { "-checked" } else { "" }
+        )
+    }
+}
+
+impl ResultCache {
+    pub fn new<P>(dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<AocRes> {
+        let contents = fs::read_to_string(self.dir.join(key.file_name())).ok()?;
+        decode(contents.trim())
+    }
+
+    pub fn put(&self, key: &CacheKey, res: AocRes) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(key.file_name()), encode(res) + "\n")
+    }
+}
+
+pub fn encode(res: AocRes) -> String {
+    match res {
+        AocRes::Int32(data) => format!("Int32 {}", data),
+        AocRes::Int64(data) => format!("Int64 {}", data),
+        AocRes::UInt32(data) => format!("UInt32 {}", data),
+        AocRes::UInt64(data) => format!("UInt64 {}", data),
+    }
+}
+
+pub fn decode(s: &str) -> Option<AocRes> {
+    let (kind, value) = s.split_once(' ')?;
+    match kind {
+        "Int32" => value.parse().ok().map(AocRes::Int32),
+        "Int64" => value.parse().ok().map(AocRes::Int64),
+        "UInt32" => value.parse().ok().map(AocRes::UInt32),
+        "UInt64" => value.parse().ok().map(AocRes::UInt64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for res in [
+            AocRes::Int32(-5),
+            AocRes::Int64(i64::MIN),
+            AocRes::UInt32(7),
+            AocRes::UInt64(u64::MAX),
+        ] {
+            assert_eq!(decode(&encode(res)), Some(res));
+        }
+        assert_eq!(decode("Float 1.0"), None);
+        assert_eq!(decode("Int32 x"), None);
+    }
+
+    #[test]
+    fn key_depends_on_input_and_version() {
+        let a = CacheKey::new(6, 2, "1", b"Time: 7\n");
+        assert_eq!(a, CacheKey::new(6, 2, "1", b"Time: 7\n"));
+        assert_ne!(a, CacheKey::new(6, 2, "1", b"Time: 8\n"));
+        assert_ne!(a.file_name(), CacheKey::new(6, 2, "2", b"Time: 7\n").file_name());
+        assert_ne!(a.file_name(), CacheKey::new(6, 1, "1", b"Time: 7\n").file_name());
+    }
+
+    #[test]
+    fn checked_runs_do_not_share_answers_with_unchecked_ones() {
+        let unchecked = CacheKey { checked: false, ..CacheKey::new(9, 1, "1", b"7 1517886983\n") };
+        let checked = CacheKey { checked: true, ..unchecked.clone() };
+        assert_ne!(unchecked.file_name(), checked.file_name());
+        assert!(checked.file_name().ends_with("-checked"));
+    }
+
+    #[test]
+    fn stores_and_returns_answers() {
+        let dir = std::env::temp_dir().join(format!("aoc-cache-test-{}", std::process::id()));
+        let cache = ResultCache::new(&dir);
+        let key = CacheKey::new(1, 1, "1", b"1abc2\n");
+        assert_eq!(cache.get(&key), None);
+        cache.put(&key, AocRes::Int64(12)).unwrap();
+        assert_eq!(cache.get(&key), Some(AocRes::Int64(12)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,271 @@
+//! Strict validation of puzzle inputs.
+//!
+//! The solvers stop at the first problem in their input. Each day has a
+//! `check_input` that instead goes through every line with the same parsing
+//! the solver uses, adds the rules the solver takes for granted, and collects
+//! every problem it finds; this module turns those into diagnostics pointing
+//! at the offending line and column.
+
+use crate::input;
+use crate::{Day1, Day10, Day2, Day3, Day4, Day5, Day6, Day7, Day8, Day9};
+use std::fmt;
+use std::fs;
+use std::io;
+
+/// A problem on one line of an input. `line` and `column` are 0-based,
+/// `column` counts characters and `len` is how many of them are wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(line: usize, column: usize, len: usize, message: String) -> Self {
+        Self {
+            line,
+            column,
+            len,
+            message,
+        }
+    }
+
+    /// Points at all of `line`.
+    pub fn whole_line(line: usize, text: &str, message: String) -> Self {
+        Self::new(line, 0, text.chars().count(), message)
+    }
+}
+
+pub struct Diagnostic {
+    pub path: String,
+    pub source_line: String,
+    pub error: InputError,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line_no = (self.error.line + 1).to_string();
+        let gutter = " ".repeat(line_no.len());
+        writeln!(
+            f,
+            "{}:{}:{}: {}",
+            self.path,
+            self.error.line + 1,
+            self.error.column + 1,
+            self.error.message
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_no, self.source_line)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.error.column),
+            "^".repeat(self.error.len.max(1))
+        )
+    }
+}
+
+/// Runs the strict parser of `day` over `lines`, or `None` if the day has none.
+pub fn check_lines(day: u32, lines: &[&str]) -> Option<Vec<InputError>> {
+    let errors = match day {
+        1 => Day1::check_input(lines),
+        2 => Day2::check_input(lines),
+        3 => Day3::check_input(lines),
+        4 => Day4::check_input(lines),
+        5 => Day5::check_input(lines),
+        6 => Day6::check_input(lines),
+        7 => Day7::check_input(lines),
+        8 => Day8::check_input(lines),
+        9 => Day9::check_input(lines),
+        10 => Day10::check_input(lines),
+        _ => return None,
+    };
+    Some(errors)
+}
+
+/// Checks the input at `path` for `day`, returning one diagnostic per problem.
+pub fn check_file(day: u32, path: &str) -> io::Result<Vec<Diagnostic>> {
+    let contents = fs::read_to_string(path)?;
+    let lines = input::lines(&contents);
+    let Some(mut errors) = check_lines(day, &lines) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("day {} has no input checker", day),
+        ));
+    };
+    errors.sort_by_key(|e| (e.line, e.column));
+    Ok(errors
+        .into_iter()
+        .map(|error| Diagnostic {
+            path: path.to_string(),
+            source_line: lines.get(error.line).unwrap_or(&"").to_string(),
+            error,
+        })
+        .collect())
+}
+
+/// Character column of the byte offset `byte` in `text`.
+pub fn column_of(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
+/// Checks that every row of a grid is as wide as the first one.
+pub fn check_grid_width(lines: &[&str]) -> Vec<InputError> {
+    let mut errors = Vec::new();
+    let Some(first) = lines.first() else {
+        return vec![InputError::new(0, 0, 0, "input is empty".to_string())];
+    };
+    let width = first.chars().count();
+    for (i, line) in lines.iter().enumerate().skip(1) {
+        let len = line.chars().count();
+        if len != width {
+            errors.push(InputError::new(
+                i,
+                len.min(width),
+                len.abs_diff(width),
+                format!("row is {} wide, the first row is {}", len, width),
+            ));
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_caret_under_the_problem() {
+        let diagnostic = Diagnostic {
+            path: "inputs/day7.input".to_string(),
+            source_line: "32TX3 765".to_string(),
+            error: InputError::new(2, 3, 1, "unknown card 'X'".to_string()),
+        };
+        assert_eq!(
+            diagnostic.to_string(),
+            "inputs/day7.input:3:4: unknown card 'X'\n  |\n3 | 32TX3 765\n  |    ^"
+        );
+    }
+
+    #[test]
+    fn reports_every_bad_line_of_day7_and_day8() {
+        let errors = check_lines(7, &["32T3K 765", "32T3KK 1", "32TX3 10", "AAAAA"]).unwrap();
+        let found: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 0, "hand must have 5 cards, found 6"),
+                (2, 3, "unknown card 'X'"),
+                (3, 0, "expected a hand and a bid like '32T3K 765'"),
+            ]
+        );
+
+        let lines = [
+            "LRX",
+            "",
+            "AAA = (BBB, CCC)",
+            "BBB = (AAA,CCC)",
+            "CCC = (AAA, DDD)",
+        ];
+        let errors = check_lines(8, &lines).unwrap();
+        let found: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, 2, "unknown direction 'X'"),
+                (3, 0, "expected a node like 'AAA = (BBB, CCC)'"),
+                (2, 7, "node 'BBB' is never defined"),
+                (4, 12, "node 'DDD' is never defined"),
+            ]
+        );
+    }
+
+    #[test]
+    fn points_into_games_and_cards() {
+        let errors = check_lines(2, &["Game 1: 3 blue; 4 red, 1 pink", "Game x: 1 red"]).unwrap();
+        let found: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, 25, "expected red, green or blue, found 'pink'"),
+                (1, 5, "expected i64 for id, found 'x'"),
+            ]
+        );
+
+        let errors = check_lines(4, &["Card 1: 41 48 | 83 8x", "Card 2: 41 48 83"]).unwrap();
+        let found: Vec<(usize, usize, &str)> = errors
+            .iter()
+            .map(|e| (e.line, e.column, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, 19, "expected i32 for have, found '8x'"),
+                (1, 8, "expected ' | ' after winning"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_walks_that_never_end() {
+        let lines = ["LR", "", "AAA = (BBB, BBB)", "BBB = (AAA, AAA)", "ZZZ = (ZZZ, ZZZ)"];
+        let errors = check_lines(8, &lines).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[0].message, "walking from 'AAA' never reaches an end node");
+
+        let errors = check_lines(10, &["S-7..", "|.|..", "L-J.."]).unwrap();
+        assert!(errors.is_empty());
+        let errors = check_lines(10, &["S-7..", "|.-..", "L-J.."]).unwrap();
+        assert_eq!(
+            (errors[0].line, errors[0].column, errors[0].message.as_str()),
+            (1, 2, "the loop from 'S' is broken here")
+        );
+    }
+
+    #[test]
+    fn solvers_reject_what_the_checker_reports() {
+        let path = std::env::temp_dir().join(format!("aoc-check-parse-{}", std::process::id()));
+        let path_to_input = path.to_str().unwrap();
+        for (day, text) in [
+            (2, "Game 1: 3 blue; 4 red, 1 pink"),
+            (4, "Card 1: 41 48 | 83 8x"),
+            (5, "seeds: 79 14\n\nseed-to-soil map:\n50 98"),
+            (6, "Time: 7 15\nDistance: 9 x"),
+            (7, "32TX3 10"),
+            (8, "LRX\n\nAAA = (AAA, AAA)"),
+            (9, "0 3 6 x"),
+        ] {
+            fs::write(&path, text).unwrap();
+            let errors = check_lines(day, &input::lines(text)).unwrap();
+            let error = crate::solver_for_day(day, path_to_input).unwrap().parse().unwrap_err();
+            assert!(error.contains(&errors[0].message), "day {}: {}", day, error);
+        }
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn every_stored_input_is_clean() {
+        for day in 1..=crate::NUM_DAYS {
+            let path = crate::input_path_for_day(day);
+            let diagnostics = check_file(day, &path).unwrap();
+            assert!(
+                diagnostics.is_empty(),
+                "{}",
+                diagnostics.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n")
+            );
+        }
+    }
+}
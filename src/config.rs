@@ -0,0 +1,341 @@
+//! Settings from `aoc.toml`, the environment and the command line.
+//!
+//! `Config::load` starts from defaults that match running from the repository
+//! root, then applies the first `aoc.toml` found walking up from the current
+//! directory (or the file named by `--config` or `AOC_CONFIG`), then `AOC_*` environment
+//! variables such as `AOC_THREADS=4`. Command line flags go on top through
+//! `Config::apply`. Relative paths in `aoc.toml` are relative to the file, so
+//! the tools work the same from any subdirectory.
+//!
+//! ```toml
+//! inputs = "inputs"
+//! tasks = "tasks"
+//! cache = ".aoc-cache"
+//! history = ".aoc-history"
+//! year = 2023
+//! days = [1, 2, 3]   # run when no day is given, all of them when empty
+//! verbosity = 1      # 0 quiet, 1 progress bars, 2 timings too
+//! threads = 4
+//! timeout = 30       # seconds per part, 0 for none
+//! output = "text"    # or "json"
+//! ```
+
+use crate::aoc::AocError;
+use crate::cache::DEFAULT_CACHE_DIR;
+use crate::history::DEFAULT_HISTORY_FILE;
+use crate::NUM_DAYS;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+pub const FILE_NAME: &str = "aoc.toml";
+
+/// The only year with solvers so far.
+pub const YEAR: u32 = 2023;
+
+/// Every setting, by its name in `aoc.toml`.
+pub const KEYS: [&str; 10] = [
+    "inputs",
+    "tasks",
+    "cache",
+    "history",
+    "year",
+    "days",
+    "verbosity",
+    "threads",
+    "timeout",
+    "output",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The `aoc.toml` that was applied, if any
+    pub file: Option<PathBuf>,
+    pub inputs: PathBuf,
+    pub tasks: PathBuf,
+    pub cache: PathBuf,
+    /// The log of runs, see `history`
+    pub history: PathBuf,
+    pub year: u32,
+    /// Days to run when none are given, all of them when empty
+    pub days: Vec<u32>,
+    /// 0 prints only answers and errors, 1 adds progress bars, 2 adds timings
+    pub verbosity: u8,
+    /// Workers for `parallel`, 1 runs everything in order on the calling thread
+    pub threads: usize,
+    /// How long one part may run, `None` for no limit
+    pub timeout: Option<Duration>,
+    pub output: OutputFormat,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            file: None,
+            inputs: PathBuf::from("./inputs"),
+            tasks: PathBuf::from("./tasks"),
+            cache: PathBuf::from(DEFAULT_CACHE_DIR),
+            history: PathBuf::from(DEFAULT_HISTORY_FILE),
+            year: YEAR,
+            days: Vec::new(),
+            verbosity: 1,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            timeout: None,
+            output: OutputFormat::Text,
+        }
+    }
+}
+
+impl Config {
+    /// The defaults, then `file` or else the `aoc.toml` in effect, then the
+    /// environment.
+    pub fn load(file: Option<&Path>) -> Result<Self, AocError> {
+        let mut config = Self::default();
+        let file = match (file, env::var_os("AOC_CONFIG")) {
+            (Some(file), _) => Some(file.to_path_buf()),
+            (None, Some(path)) => Some(PathBuf::from(path)),
+            (None, None) => env::current_dir()
+                .ok()
+                .and_then(|dir| Self::find_file(&dir)),
+        };
+        if let Some(file) = file {
+            config.apply_file(&file)?;
+        }
+        config.apply_env(env::vars())?;
+        Ok(config)
+    }
+
+    /// The first `aoc.toml` in `start` or one of its parents.
+    pub fn find_file(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub fn apply_file(&mut self, path: &Path) -> Result<(), AocError> {
+        let error = |reason: String| AocError::Config {
+            source: path.display().to_string(),
+            reason,
+        };
+        let text = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| error(e.message().to_string()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for (key, value) in &table {
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Array(items) => {
+                    let items: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+                    items.join(",")
+                }
+                other => return Err(error(format!("{}: unexpected {}", key, other.type_str()))),
+            };
+            self.apply_in(key, &value, base)
+                .map_err(|reason| error(format!("{}: {}", key, reason)))?;
+        }
+        self.file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Applies `AOC_INPUTS`, `AOC_THREADS` and so on for every key in `KEYS`.
+    pub fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), AocError> {
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix("AOC_").map(str::to_lowercase) else {
+                continue;
+            };
+            if !KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            self.apply(&key, &value)
+                .map_err(|reason| AocError::Config {
+                    source: name,
+                    reason,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Sets `key` from its text form, relative paths staying relative to the
+    /// current directory.
+    pub fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.apply_in(key, value, Path::new(""))
+    }
+
+    fn apply_in(&mut self, key: &str, value: &str, base: &Path) -> Result<(), String> {
+        let value = value.trim();
+        let number = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("expected a whole number, found '{}'", value))
+        };
+        match key {
+            "inputs" => self.inputs = base.join(value),
+            "tasks" => self.tasks = base.join(value),
+            "cache" => self.cache = base.join(value),
+            "history" => self.history = base.join(value),
+            "year" => {
+                let year = number(value)? as u32;
+                if year != YEAR {
+                    return Err(format!("there are only solvers for {}", YEAR));
+                }
+                self.year = year;
+            }
+            "days" => {
+                let mut days = Vec::new();
+                for day in value.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+                    match day.parse::<u32>() {
+                        Ok(day) if (1..=NUM_DAYS).contains(&day) => days.push(day),
+                        _ => return Err(format!("'{}' is not a day from 1 to {}", day, NUM_DAYS)),
+                    }
+                }
+                self.days = days;
+            }
+            "verbosity" => {
+                self.verbosity = match value {
+                    "quiet" => 0,
+                    "normal" => 1,
+                    "verbose" => 2,
+                    _ => match number(value)? {
+                        level @ 0..=2 => level as u8,
+                        _ => return Err("expected 0, 1 or 2".to_string()),
+                    },
+                };
+            }
+            "threads" => match number(value)? {
+                0 => return Err("needs at least one thread".to_string()),
+                threads => self.threads = threads as usize,
+            },
+            "timeout" => {
+                let seconds = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|s| s.is_finite() && *s >= 0.0)
+                    .ok_or_else(|| format!("expected seconds, found '{}'", value))?;
+                self.timeout = (seconds > 0.0).then(|| Duration::from_secs_f64(seconds));
+            }
+            "output" => {
+                self.output = match value {
+                    "text" => OutputFormat::Text,
+                    "json" => OutputFormat::Json,
+                    _ => return Err(format!("expected text or json, found '{}'", value)),
+                };
+            }
+            _ => {
+                return Err(format!(
+                    "unknown setting, expected one of {}",
+                    KEYS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        self.inputs.join(format!("day{}.input", day))
+    }
+
+    pub fn example_input_path(&self, day: u32) -> PathBuf {
+        self.inputs.join(format!("day{}_test.input", day))
+    }
+
+    pub fn task_path(&self, day: u32) -> PathBuf {
+        self.tasks.join(format!("day{}.txt", day))
+    }
+}
+
+static CURRENT: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+/// The settings in effect, the defaults until `set` is called.
+pub fn current() -> Arc<Config> {
+    let current = CURRENT.read().unwrap_or_else(|e| e.into_inner());
+    current.clone().unwrap_or_default()
+}
+
+pub fn set(config: Config) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(config));
+}
+
+/// Changes the settings in effect, keeping them as they were on an error.
+pub fn update(change: impl FnOnce(&mut Config) -> Result<(), String>) -> Result<(), String> {
+    let mut config = (*current()).clone();
+    change(&mut config)?;
+    set(config);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_file_environment_and_flags() {
+        let dir = env::temp_dir().join(format!("aoc-config-{}", std::process::id()));
+        let nested = dir.join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            dir.join(FILE_NAME),
+            "inputs = \"puzzles\"\ndays = [3, 1]\nthreads = 2\ntimeout = 1.5\noutput = \"json\"\n",
+        )
+        .unwrap();
+        let file = Config::find_file(&nested).unwrap();
+        assert_eq!(file, dir.join(FILE_NAME));
+
+        let mut config = Config::default();
+        config.apply_file(&file).unwrap();
+        assert_eq!(config.input_path(7), dir.join("puzzles/day7.input"));
+        assert_eq!(config.days, [3, 1]);
+        assert_eq!(config.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(config.output, OutputFormat::Json);
+
+        let vars = [
+            ("AOC_THREADS", "6"),
+            ("AOC_VERBOSITY", "quiet"),
+            ("HOME", "/"),
+        ];
+        config
+            .apply_env(vars.map(|(k, v)| (k.to_string(), v.to_string())))
+            .unwrap();
+        assert_eq!((config.threads, config.verbosity), (6, 0));
+        config.apply("threads", "1").unwrap();
+        config.apply("timeout", "0").unwrap();
+        assert_eq!((config.threads, config.timeout), (1, None));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejects_bad_settings() {
+        let mut config = Config::default();
+        assert!(config.apply("threads", "0").is_err());
+        assert!(config.apply("days", "1,11").is_err());
+        assert!(config.apply("year", "2022").is_err());
+        assert!(config.apply("output", "yaml").is_err());
+        assert!(config.apply("colour", "on").is_err());
+        assert_eq!(config, Config::default());
+
+        let path = env::temp_dir().join(format!("aoc-config-bad-{}.toml", std::process::id()));
+        fs::write(&path, "threads = 0\n").unwrap();
+        let error = config.apply_file(&path).unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("threads: needs at least one thread"));
+        fs::write(&path, "threads = [\n").unwrap();
+        assert!(config.apply_file(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,497 @@
+//! Full-screen terminal dashboard listing every day and the state of its parts.
+//!
+//! Parts run on background threads and report back over a channel, so the
+//! table keeps redrawing while long parts are still going. An answer counts as
+//! passing when it matches the known answer recorded next to the input, in
+//! `dayN.answers` for `dayN.input`, with a line like `part1: Int32 288` per
+//! part. Parts without one are shown as unverified.
+
+use crate::aoc::AocRes;
+use crate::cache::decode;
+use crate::fuzz::panic_message;
+use crate::progress;
+use crate::runner::run_part;
+use crate::{
+    example_input_path_for_day, input_path_for_day, solver_for_day, task_path_for_day,
+    title_for_day, NUM_DAYS,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const KEYS: &str =
+    "up/down: day  left/right: part  enter: run  a: run all  e: example input  t: puzzle text  q: quit";
+
+// Parts run on threads with this name, their panics are reported in the table
+const PART_THREAD: &str = "dashboard part";
+
+/// The answer recorded for `part` of the input at `path_to_input`, if any.
+pub fn known_answer(path_to_input: &str, part: u32) -> Option<AocRes> {
+    let path = Path::new(path_to_input).with_extension("answers");
+    let text = fs::read_to_string(path).ok()?;
+    let prefix = format!("part{}:", part);
+    text.lines()
+        .find_map(|line| line.strip_prefix(&prefix))
+        .and_then(|answer| decode(answer.trim()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartState {
+    Unsolved,
+    Running,
+    Passing,
+    /// Solved, but there is no known answer to compare with
+    Unverified,
+    Failing(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PartStatus {
+    pub state: PartState,
+    pub duration: Option<Duration>,
+    pub answer: Option<AocRes>,
+}
+
+pub struct DayRow {
+    pub day: u32,
+    pub title: &'static str,
+    pub use_example: bool,
+    pub parts: [PartStatus; 2],
+}
+
+impl DayRow {
+    fn path_to_input(&self) -> String {
+        if self.use_example {
+            example_input_path_for_day(self.day)
+        } else {
+            input_path_for_day(self.day)
+        }
+    }
+}
+
+struct Finished {
+    day: u32,
+    part: u32,
+    // answer, how long it took and the known answer
+    result: Result<(AocRes, Duration, Option<AocRes>), String>,
+}
+
+enum View {
+    Table,
+    Task { lines: Vec<String>, scroll: usize },
+}
+
+pub struct Dashboard {
+    pub rows: Vec<DayRow>,
+    selected: usize,
+    part: u32,
+    view: View,
+    message: String,
+    sender: Sender<Finished>,
+    receiver: Receiver<Finished>,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dashboard {
+    pub fn new() -> Self {
+        let rows = (1..=NUM_DAYS)
+            .map(|day| DayRow {
+                day,
+                title: title_for_day(day).unwrap_or(""),
+                use_example: false,
+                parts: [0, 1].map(|_| PartStatus {
+                    state: PartState::Unsolved,
+                    duration: None,
+                    answer: None,
+                }),
+            })
+            .collect();
+        let (sender, receiver) = channel();
+        Self {
+            rows,
+            selected: 0,
+            part: 1,
+            view: View::Table,
+            message: String::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Takes over the terminal until the user quits.
+    pub fn run(&mut self) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        let _terminal = TerminalGuard::take_over()?;
+        self.event_loop(&mut stdout)
+    }
+
+    fn event_loop<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        loop {
+            self.poll();
+            self.draw(out)?;
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns false when the user asked to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let View::Task { lines, scroll } = &mut self.view {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(lines.len()),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(20),
+                KeyCode::PageDown => *scroll = (*scroll + 20).min(lines.len()),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => self.view = View::Table,
+                _ => (),
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.rows.len() - 1)
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.part = 1,
+            KeyCode::Right | KeyCode::Char('l') => self.part = 2,
+            KeyCode::Enter | KeyCode::Char('r') => self.run_selected(),
+            KeyCode::Char('a') => {
+                for row in 0..self.rows.len() {
+                    self.spawn_part(row, 1);
+                    self.spawn_part(row, 2);
+                }
+            }
+            KeyCode::Char('e') => self.toggle_example(),
+            KeyCode::Char('t') => self.open_task(),
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => (),
+        }
+        true
+    }
+
+    pub fn run_selected(&mut self) {
+        self.spawn_part(self.selected, self.part);
+    }
+
+    pub fn select(&mut self, day: u32, part: u32) {
+        if let Some(row) = self.rows.iter().position(|r| r.day == day) {
+            self.selected = row;
+        }
+        self.part = part;
+    }
+
+    pub fn toggle_example(&mut self) {
+        let row = &mut self.rows[self.selected];
+        if !row.use_example && !Path::new(&example_input_path_for_day(row.day)).is_file() {
+            self.message = format!("day {} has no example input", row.day);
+            return;
+        }
+        row.use_example = !row.use_example;
+        for part in &mut row.parts {
+            if part.state != PartState::Running {
+                part.state = PartState::Unsolved;
+                part.duration = None;
+                part.answer = None;
+            }
+        }
+        self.message = format!("day {} now uses {}", row.day, row.path_to_input());
+    }
+
+    fn open_task(&mut self) {
+        let path = task_path_for_day(self.rows[self.selected].day);
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                self.view = View::Task {
+                    lines: text.lines().map(|l| l.to_string()).collect(),
+                    scroll: 0,
+                }
+            }
+            Err(e) => self.message = format!("cannot open {}: {}", path, e),
+        }
+    }
+
+    fn spawn_part(&mut self, row: usize, part: u32) {
+        let row = &mut self.rows[row];
+        let day = row.day;
+        let path_to_input = row.path_to_input();
+        let status = &mut row.parts[part as usize - 1];
+        if status.state == PartState::Running {
+            return;
+        }
+        status.state = PartState::Running;
+        let sender = self.sender.clone();
+        let spawned = thread::Builder::new()
+            .name(PART_THREAD.to_string())
+            .spawn(move || {
+                let result = panic::catch_unwind(|| {
+                    let solver = solver_for_day(day, &path_to_input).unwrap();
+                    let run = run_part(solver.as_ref(), day, part, &path_to_input, None)
+                        .map_err(|e| e.to_string())?;
+                    Ok((run.answer, run.duration, known_answer(&path_to_input, part)))
+                })
+                .map_err(|payload| panic_message(payload.as_ref()))
+                .and_then(|run| run);
+                let _ = sender.send(Finished { day, part, result });
+            });
+        if let Err(e) = spawned {
+            status.state = PartState::Failing(e.to_string());
+        }
+    }
+
+    /// Applies the results of every part that finished since the last call.
+    pub fn poll(&mut self) {
+        while let Ok(finished) = self.receiver.try_recv() {
+            let Some(row) = self.rows.iter_mut().find(|r| r.day == finished.day) else {
+                continue;
+            };
+            let status = &mut row.parts[finished.part as usize - 1];
+            match finished.result {
+                Ok((answer, duration, expected)) => {
+                    status.state = match expected {
+                        Some(expected) if expected != answer => {
+                            PartState::Failing(format!("expected {}", expected))
+                        }
+                        Some(_) => PartState::Passing,
+                        None => PartState::Unverified,
+                    };
+                    status.duration = Some(duration);
+                    status.answer = Some(answer);
+                }
+                Err(msg) => {
+                    status.state = PartState::Failing(msg);
+                    status.duration = None;
+                    status.answer = None;
+                }
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.rows
+            .iter()
+            .any(|r| r.parts.iter().any(|p| p.state == PartState::Running))
+    }
+
+    pub fn table_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:>3}  {:<32} {:<7}  {:<40}  {:<40}",
+            "day", "title", "input", "part 1", "part 2"
+        )];
+        for row in &self.rows {
+            let parts: Vec<String> = row.parts.iter().map(Self::part_cell).collect();
+            lines.push(format!(
+                "{:>3}  {:<32} {:<7}  {:<40}  {:<40}",
+                row.day,
+                row.title,
+                if row.use_example { "example" } else { "full" },
+                parts[0],
+                parts[1]
+            ));
+        }
+        lines
+    }
+
+    fn part_cell(status: &PartStatus) -> String {
+        let state = match &status.state {
+            PartState::Unsolved => "unsolved".to_string(),
+            PartState::Running => "running".to_string(),
+            PartState::Passing => "passing".to_string(),
+            PartState::Unverified => "unverified".to_string(),
+            PartState::Failing(why) => format!("FAILING ({})", why),
+        };
+        let answer = status.answer.map(|a| a.to_string()).unwrap_or_default();
+        let duration = status
+            .duration
+            .map(|d| format!("{:.1?}", d))
+            .unwrap_or_default();
+        let cell = format!("{:<8} {:>15} {:>10}", state, answer, duration);
+        // Long failure messages would push the next column off screen
+        cell.chars().take(40).collect()
+    }
+
+    fn draw<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        queue!(out, terminal::Clear(ClearType::All))?;
+        match &self.view {
+            View::Table => {
+                for (i, line) in self.table_lines().iter().enumerate().take(height) {
+                    let line: Vec<char> = line.chars().take(width).collect();
+                    queue!(out, cursor::MoveTo(0, i as u16))?;
+                    if i == self.selected + 1 {
+                        // Highlight the row, and the selected part a second time
+                        let split = 3 + 2 + 32 + 1 + 7 + 2 + if self.part == 1 { 0 } else { 42 };
+                        let split = split.min(line.len());
+                        let end = (split + 40).min(line.len());
+                        queue!(
+                            out,
+                            SetAttribute(Attribute::Reverse),
+                            Print(line[..split].iter().collect::<String>()),
+                            SetAttribute(Attribute::Bold),
+                            Print(line[split..end].iter().collect::<String>()),
+                            SetAttribute(Attribute::NormalIntensity),
+                            Print(line[end..].iter().collect::<String>()),
+                            SetAttribute(Attribute::Reset)
+                        )?;
+                    } else {
+                        queue!(out, Print(line.iter().collect::<String>()))?;
+                    }
+                }
+                let footer = format!("{}  {}", KEYS, self.message);
+                let footer: String = footer.chars().take(width).collect();
+                queue!(out, cursor::MoveTo(0, height.saturating_sub(1) as u16), Print(footer))?;
+            }
+            View::Task { lines, scroll } => {
+                for (i, line) in lines.iter().skip(*scroll).take(height.saturating_sub(1)).enumerate() {
+                    let line: String = line.chars().take(width).collect();
+                    queue!(out, cursor::MoveTo(0, i as u16), Print(line))?;
+                }
+                queue!(
+                    out,
+                    cursor::MoveTo(0, height.saturating_sub(1) as u16),
+                    Print("up/down/pgup/pgdn: scroll  t/esc: back")
+                )?;
+            }
+        }
+        out.flush()
+    }
+}
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// Raw mode and the alternate screen while the dashboard runs. Dropping it puts
+/// the terminal and the panic hook back, also when the dashboard panics.
+struct TerminalGuard {
+    default_hook: Arc<PanicHook>,
+}
+
+impl TerminalGuard {
+    fn take_over() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let default_hook: Arc<PanicHook> = Arc::new(panic::take_hook());
+        let guard = Self {
+            default_hook: default_hook.clone(),
+        };
+        // Panicking parts are reported in the table, not over the top of it.
+        // Anything else gets the terminal back first so its message stays readable
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().name() != Some(PART_THREAD) {
+                restore_terminal();
+                default_hook(info);
+            }
+        }));
+        // Parts draw their progress bars on stderr, which is the screen the table is on
+        progress::suppress_bar(true);
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+        progress::suppress_bar(false);
+        let _ = panic::take_hook();
+        let default_hook = self.default_hook.clone();
+        panic::set_hook(Box::new(move |info| default_hook(info)));
+    }
+}
+
+fn restore_terminal() {
+    let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn wait_for_parts(dashboard: &mut Dashboard) {
+        let start = Instant::now();
+        while dashboard.is_running() {
+            assert!(start.elapsed() < Duration::from_secs(30), "parts never finished");
+            thread::sleep(Duration::from_millis(10));
+            dashboard.poll();
+        }
+    }
+
+    #[test]
+    fn runs_parts_in_the_background_and_checks_against_known_answers() {
+        let mut dashboard = Dashboard::new();
+        dashboard.select(8, 1);
+        dashboard.toggle_example();
+        assert!(dashboard.rows[7].use_example);
+
+        dashboard.run_selected();
+        assert_eq!(dashboard.rows[7].parts[0].state, PartState::Running);
+        dashboard.select(8, 2);
+        dashboard.run_selected();
+        wait_for_parts(&mut dashboard);
+        assert_eq!(dashboard.rows[7].parts[0].state, PartState::Passing);
+        assert_eq!(dashboard.rows[7].parts[0].answer, Some(AocRes::Int32(6)));
+        // The example of part 1 has no known answer for part 2
+        assert_eq!(dashboard.rows[7].parts[1].state, PartState::Unverified);
+
+        // A wrong answer turns the part red
+        dashboard
+            .sender
+            .send(Finished {
+                day: 8,
+                part: 1,
+                result: Ok((AocRes::Int32(6), Duration::ZERO, Some(AocRes::Int32(7)))),
+            })
+            .unwrap();
+        dashboard.poll();
+        assert_eq!(
+            dashboard.rows[7].parts[0].state,
+            PartState::Failing("expected 7".to_string())
+        );
+        assert!(dashboard.table_lines()[8].contains("FAILING"));
+    }
+
+    #[test]
+    fn reads_known_answers_next_to_the_input() {
+        assert_eq!(known_answer("./inputs/day5.input", 2), Some(AocRes::UInt64(31161857)));
+        assert_eq!(known_answer("./inputs/day6_test.input", 1), Some(AocRes::Int32(288)));
+        assert_eq!(known_answer("./inputs/day2.input", 2), None);
+        assert_eq!(known_answer("./inputs/day5_test.input", 1), None);
+    }
+
+    #[test]
+    fn lists_every_day() {
+        let dashboard = Dashboard::new();
+        let lines = dashboard.table_lines();
+        assert_eq!(lines.len(), NUM_DAYS as usize + 1);
+        assert!(lines[7].contains("Camel Cards"));
+        assert!(lines[7].contains("unsolved"));
+    }
+
+    #[test]
+    fn example_input_is_only_offered_when_saved() {
+        let mut dashboard = Dashboard::new();
+        dashboard.select(1, 1);
+        dashboard.toggle_example();
+        assert!(!dashboard.rows[0].use_example);
+    }
+}
@@ -1,9 +1,10 @@
 use crate::aoc::{Aoc, AocRes};
+use crate::input;
+use crate::check::InputError;
+use crate::explain::Explanation;
+use crate::scan::ScanError;
 use regex::Regex;
 use std::collections::BinaryHeap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
 
 pub struct Day1 {
     path_to_input: String
@@ -11,28 +12,21 @@ pub struct Day1 {
 
 impl Aoc for Day1 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self{
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
         let mut answer: i64 = 0;
-        if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-            // Consumes the iterator, returns an (Optional) String
-            for line in lines {
-                if let Ok(ip) = line {
-                    let first = Self::find_first(ip.clone());
-                    let mut char_vector: Vec<char> = ip.chars().collect();
-                    char_vector.reverse();
-                    let reverse_ip: String = char_vector.into_iter().collect();
-                    let last = Self::find_first(reverse_ip.clone());
-                    let number = format!("{first}{last}");
-                    // println!("{number}");
-                    let number = number.parse::<i64>().unwrap();
-                    answer = answer + number;
-                }
+        if let Ok(lines) = input::read_lines(&self.path_to_input) {
+            for ip in lines {
+                let (first, last) = Self::first_and_last_part1(&ip);
+                let number = format!("{first}{last}");
+                // println!("{number}");
+                let number = number.parse::<i64>().unwrap();
+                answer += number;
             }
         }
 
@@ -40,70 +34,120 @@ impl Aoc for Day1 {
     }
 
     fn part2(&self) -> AocRes {
-        let numbers = [
-            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "0",
-            "1", "2", "3", "4", "5", "6", "7", "8", "9",
-        ];
+        let number_res = Self::number_regexes();
         let mut answer: i64 = 0;
-        if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-            // Consumes the iterator, returns an (Optional) String
-            for line in lines {
-                if let Ok(ip) = line {
-                    let mut last_num_heap = BinaryHeap::<(i32, i32)>::new();
-                    let mut first_num_heap = BinaryHeap::<(i32, i32)>::new();
-                    for number in numbers {
-                        let formatted = format!(r"({})", number);
-                        let re = Regex::new(formatted.as_str()).unwrap();
-                        for m in re.find_iter(ip.clone().as_str()) {
-                            let val = match m.as_str() {
-                                "zero" => "0",
-                                "one" => "1",
-                                "two" => "2",
-                                "three" => "3",
-                                "four" => "4",
-                                "five" => "5",
-                                "six" => "6",
-                                "seven" => "7",
-                                "eight" => "8",
-                                "nine" => "9",
-                                _any => _any,
-                            };
-                            first_num_heap
-                                .push((m.start() as i32 * -1, val.parse::<i32>().unwrap()));
-                            last_num_heap.push((m.start() as i32, val.parse::<i32>().unwrap()));
-                        }
-                    }
-                    let (_pos, first) = first_num_heap.peek().unwrap();
-                    let (_pos, last) = last_num_heap.peek().unwrap();
-                    let number = format!("{first}{last}");
-                    // println!("{number}");
-                    let number = number.parse::<i64>().unwrap();
-                    answer = answer + number;
-                }
+        if let Ok(lines) = input::read_lines(&self.path_to_input) {
+            for ip in lines {
+                let (first, last) = Self::first_and_last_part2(&ip, &number_res);
+                let number = format!("{first}{last}");
+                // println!("{number}");
+                let number = number.parse::<i64>().unwrap();
+                answer += number;
             }
         }
 
         AocRes::Int64(answer)
     }
+
+    fn explain(&self, part: u32) -> Option<Explanation> {
+        let answer = match part {
+            1 => self.part1(),
+            2 => self.part2(),
+            _ => return None,
+        };
+        let number_res = Self::number_regexes();
+        let mut explanation = Explanation::new(&["line", "first", "last", "value"], answer);
+        let lines = input::read_lines(&self.path_to_input).ok()?;
+        for ip in lines {
+            let (first, last) = match part {
+                1 => {
+                    let (first, last) = Self::first_and_last_part1(&ip);
+                    (first.parse::<i32>().unwrap(), last.parse::<i32>().unwrap())
+                }
+                _ => Self::first_and_last_part2(&ip, &number_res),
+            };
+            explanation.push(vec![ip.into(), first.into(), last.into(), (first * 10 + last).into()]);
+        }
+        Some(explanation)
+    }
 }
 
 impl Day1 {
 
-    fn find_first(ip: String) -> String {
-        let re = Regex::new(r"(\d)").unwrap();
-        let Some(caps) = re.captures(ip.as_str()) else {
-            panic!("Line does not contain any numbers!");
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let error = Self::try_first_and_last_part1(line).err()?;
+                Some(error.to_input_error(i, line))
+            })
+            .collect()
+    }
+
+    fn first_and_last_part1(ip: &str) -> (String, String) {
+        Self::try_first_and_last_part1(ip).unwrap_or_else(|error| panic!("{}", error.message))
+    }
+
+    fn try_first_and_last_part1(ip: &str) -> Result<(String, String), ScanError> {
+        let Some(first) = Self::find_first(ip.to_string()) else {
+            let message = "line does not contain any digits".to_string();
+            return Err(ScanError::new(0, ip.len(), message));
         };
-        return caps[0].to_string();
+        let mut char_vector: Vec<char> = ip.chars().collect();
+        char_vector.reverse();
+        let reverse_ip: String = char_vector.into_iter().collect();
+        let last = Self::find_first(reverse_ip.clone()).unwrap_or_else(|| first.clone());
+        Ok((first, last))
+    }
+
+    fn number_regexes() -> Vec<Regex> {
+        let numbers = [
+            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "0",
+            "1", "2", "3", "4", "5", "6", "7", "8", "9",
+        ];
+        numbers
+            .iter()
+            .map(|number| {
+                let formatted = format!(r"({})", number);
+                Regex::new(formatted.as_str()).unwrap()
+            })
+            .collect()
     }
 
-    // The output is wrapped in a Result to allow matching on errors
-    // Returns an Iterator to the Reader of the lines of the file.
-    fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where
-        P: AsRef<Path>,
-    {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
+    // Every match goes into two heaps, ordered by position, whose tops are the
+    // first and the last digit of the line
+    fn first_and_last_part2(ip: &str, number_res: &[Regex]) -> (i32, i32) {
+        let mut last_num_heap = BinaryHeap::<(i32, i32)>::new();
+        let mut first_num_heap = BinaryHeap::<(i32, i32)>::new();
+        for re in number_res {
+            for m in re.find_iter(ip) {
+                let val = match m.as_str() {
+                    "zero" => "0",
+                    "one" => "1",
+                    "two" => "2",
+                    "three" => "3",
+                    "four" => "4",
+                    "five" => "5",
+                    "six" => "6",
+                    "seven" => "7",
+                    "eight" => "8",
+                    "nine" => "9",
+                    _any => _any,
+                };
+                first_num_heap.push((-(m.start() as i32), val.parse::<i32>().unwrap()));
+                last_num_heap.push((m.start() as i32, val.parse::<i32>().unwrap()));
+            }
+        }
+        let (_pos, first) = first_num_heap.peek().unwrap();
+        let (_pos, last) = last_num_heap.peek().unwrap();
+        (*first, *last)
     }
+
+    fn find_first(ip: String) -> Option<String> {
+        let re = Regex::new(r"(\d)").unwrap();
+        let caps = re.captures(ip.as_str())?;
+        Some(caps[0].to_string())
+    }
+
 }
\ No newline at end of file
@@ -1,375 +1,284 @@
 use crate::aoc::{Aoc, AocRes};
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::sync::{Arc, Mutex, Condvar};
-use std::thread;
+use crate::input;
+use crate::check::{check_grid_width, InputError};
+use crate::graph::{self, Implicit};
+use crate::grid::{Direction, Grid, Point};
+use crate::image::CellImage;
+use crate::parallel;
+use crate::variants::Variant;
+use std::collections::HashSet;
 
 
 pub struct Day10 {
   path_to_input: String
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Coordinate {
-  x: usize,
-  y: usize
-}
-
-#[derive(Debug, Clone)]
-struct Step {
-  coord: Coordinate,
-  value: i32
-}
-
 impl Aoc for Day10 {
 
-  fn new(path_to_input: &String) -> Self {
+  fn new(path_to_input: &str) -> Self {
     Self {
-      path_to_input: path_to_input.clone()
+      path_to_input: path_to_input.to_string()
     }
   }
 
   /// Algorithm
   /// Read text file into a matrix
   /// Find S
-  /// Look around
-  /// Spin up two threads
-  /// At each step, threads report their current position
-  /// We know we have found the furthest distance when both threads report the same position
-  /// Each thread needs to know:
-  /// - current position
-  /// - previous positon
-  /// - based off symbol @ current position and previous position, can figure out where to go next
+  /// Search the pipes breadth first from S
+  /// The furthest tile is the one the search reaches last
   fn part1(&self) -> AocRes {
-
-    let mut ans: i32 = 0;
-
-    if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-      // Consumes the iterator, returns an (Optional) String
-      let mut matrix = Vec::<Vec<char>>::new();
-      for line in lines {
-        if let Ok(l) = line {
-          let row: Vec<char> = l.chars().collect();
-          matrix.push(row);
-        }
-      }
-
-      let start = Self::find_start(&matrix);
-      let possible_dirs = Self::find_possible_directions_around_start(start, &matrix);
-
-      if possible_dirs.len() > 2 {
-        panic!("Assume only 2 directions that S connects to...")
-      } else {
-
-        let initial_step = Step {
-          coord: Coordinate {
-            x: 0, y: 0
-          },
-          value: 0
-        };
-        let pair1 = Arc::new((Mutex::new(initial_step.clone()), Condvar::new()));
-        let pair2: Arc<(Mutex<Step>, Condvar)> = Arc::new((Mutex::new(initial_step.clone()), Condvar::new()));
-        let pair1_copy = Arc::clone(&pair1);
-        let pair2_copy = Arc::clone(&pair2);
-
-        let matrix_ref = Arc::new(matrix);
-        let matrix_ref_1 = Arc::clone(&matrix_ref);
-        let matrix_ref_2 = Arc::clone(&matrix_ref);
-
-        let adjacent_to_start_pos_1 = Coordinate{
-          x: possible_dirs[0].1.x, 
-          y: possible_dirs[0].1.y
-        };
-
-        let adjacent_to_start_pos_2 =  Coordinate{
-          x: possible_dirs[1].1.x, 
-          y: possible_dirs[1].1.y
-        };
-
-        let child_1 = thread::spawn(move || 
-          {
-            Self::follow_loop(1, pair1_copy, start, adjacent_to_start_pos_1,  start, matrix_ref_1, 1);
-          }
-        );
-
-        let child_2 = thread::spawn(move || 
-          {
-            Self::follow_loop(2, pair2_copy, start, adjacent_to_start_pos_2, start, matrix_ref_2, 1);
-          }
-        );
-
-        loop {
-
-          let (lock1, cvar1) = &*pair1;
-          let mut result1 = lock1.lock().unwrap();
-          while (*result1).value == 0 {
-            result1 = cvar1.wait(result1).unwrap();
-            // println!("master read value1: {}", (*result1).value);
-          }
-          let res1_copy: i32 = (*result1).value;
-          (*result1).value = 0; // allow thread to proceed
-
-
-          let (lock2, cvar2) = &*pair2;
-          let mut result2 = lock2.lock().unwrap();
-          while (*result2).value == 0 {
-            result2 = cvar2.wait(result2).unwrap();
-            // println!("master read value2: {}", (*result2).value);
-          }
-          let res2_copy: i32 = (*result2).value;
-          (*result2).value = 0; // allow thread to proceed
-
-          cvar1.notify_one();
-          cvar2.notify_one();
-
-          // If same location and same step count, then we have found the midpoint
-          if ((*result2).coord == (*result1).coord) && (res1_copy == res2_copy) {
-            println!("found solution: {}", res1_copy);
-            ans = res1_copy;
-            break;
-          }
-
-          if res1_copy == -1 || res2_copy == -1 {
-            break;
-          }
-
-        }
-
-        // let _ = child_1.join();
-        // let _ = child_2.join();
-      }    
-    }
-
-    AocRes::Int32(ans)
-
+    let Some(matrix) = Self::read_grid(&self.path_to_input) else {
+      return AocRes::Int32(0);
+    };
+    let start = Self::find_start(&matrix);
+    let furthest = graph::bfs(&Self::pipes(&matrix), start).into_values().max();
+    AocRes::Int32(furthest.unwrap_or(0) as i32)
   }
 
   fn part2(&self) -> AocRes {
     AocRes::Int32(0)
   }
 
-}
-
-enum Position {
-  North,
-  South,
-  East,
-  West,
-  NorthWest,
-  NorthEast,
-  SouthEast,
-  SouthWest
-}
-
-impl Day10 {
-  fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-  where
-      P: AsRef<Path>,
-  {
-      let file = File::open(filename)?;
-      Ok(io::BufReader::new(file).lines())
+  fn parse(&self) -> Result<(), String> {
+    Self::try_read_grid(&self.path_to_input).map(drop)
   }
 
-  fn find_start(matrix: &Vec<Vec<char>>) -> Coordinate {
-    let mut start: Coordinate = Coordinate {
-      x: 0,
-      y: 0
-    };
-    for y in 0..matrix.len() {
-      for x in 0..matrix[y].len() {
-        if matrix[y][x] == 'S' {
-          start.x = x;
-          start.y = y;
-        }        
-      }
+  fn variants(&self, part: u32) -> Vec<Variant<'_>> {
+    if part != 1 {
+      return Vec::new();
     }
-    start
+    vec![
+      Variant::new("bfs", || self.part1()),
+      // Walks the loop from both pipes next to S, one walk per worker. The
+      // furthest tile is the first one both walks reach after the same number of steps
+      Variant::new("two walks", || {
+        let Some(matrix) = Self::read_grid(&self.path_to_input) else {
+          return AocRes::Int32(0);
+        };
+        let start = Self::find_start(&matrix);
+        let possible_dirs = Self::find_possible_directions_around_start(start, &matrix);
+        if possible_dirs.len() != 2 {
+          panic!("Assume only 2 directions that S connects to...")
+        }
+        let walks = parallel::map(possible_dirs, |(_, first)| Self::walk(&matrix, start, first))
+          .unwrap_or_else(|e| e.raise());
+        // The first step is to walks[_][0]
+        let meeting = walks[0].iter().zip(&walks[1]).position(|(a, b)| a == b);
+        AocRes::Int32(meeting.map_or(0, |i| i as i32 + 1))
+      }),
+    ]
   }
 
-  fn find_possible_directions_around_start(start: Coordinate, matrix: &Vec<Vec<char>>) -> Vec::<(char, Coordinate)> {
-
-    let mut possible_starts = Vec::<(char, Coordinate)>::new();
-
-    if let Some(c) = Self::go_north(start) {
-      if Self::valid(c, matrix) && vec!['|', '7', 'F'].contains(&matrix[c.y][c.x]) {
-        possible_starts.push((matrix[c.y][c.x], c))
+  // The loop on its own in the grid, then its coordinates walking it from S
+  fn snapshot(&self) -> Option<String> {
+    let matrix = Self::read_grid(&self.path_to_input)?;
+    let main_loop = Self::main_loop(&matrix);
+    let on_loop: HashSet<Point> = main_loop.iter().copied().collect();
+    let mut out = String::new();
+    for (y, row) in matrix.rows().enumerate() {
+      for (x, symbol) in row.iter().enumerate() {
+        let p = Point { x: x as i64, y: y as i64 };
+        out.push(if on_loop.contains(&p) { *symbol } else { '.' });
       }
+      out.push('\n');
     }
-
-    if let Some(c) = Self::go_south(start) {
-      if Self::valid(c, matrix) && vec!['|', 'L', 'J'].contains(&matrix[c.y][c.x]) {
-        possible_starts.push((matrix[c.y][c.x], c))
-      }
+    out.push_str(&format!("\n{} tiles:\n", main_loop.len()));
+    for p in &main_loop {
+      out.push_str(&format!("{} {}\n", p.x, p.y));
     }
+    Some(out)
+  }
 
-    if let Some(c) = Self::go_east(start) {
-      if Self::valid(c, matrix) && vec!['-', 'J', '7'].contains(&matrix[c.y][c.x]) {
-        possible_starts.push((matrix[c.y][c.x], c))
+  fn image(&self) -> Option<CellImage> {
+    let matrix = Self::read_grid(&self.path_to_input)?;
+    let legend = [
+      ("outside", [240, 240, 240]),
+      ("inside", [250, 200, 60]),
+      ("main loop", [40, 90, 200]),
+      ("S", [200, 40, 40]),
+    ];
+    let mut image = CellImage::new(matrix.width(), matrix.height(), &legend);
+    let start = Self::find_start(&matrix);
+    let main_loop = Self::main_loop(&matrix);
+    let mut on_loop = Grid::new(matrix.width(), matrix.height(), false);
+    for &c in &main_loop {
+      on_loop[c] = true;
+    }
+    // S is a pipe like any other, going north if one of its neighbours on the loop is north of it
+    let start_goes_north = main_loop.contains(&start.step(Direction::North));
+    for (y, row) in matrix.rows().enumerate() {
+      // Scanning left to right, every loop pipe going north flips inside and outside
+      let mut inside = false;
+      for (x, symbol) in row.iter().enumerate() {
+        if on_loop[Point::new(x as i64, y as i64)] {
+          let goes_north = match symbol {
+            'S' => start_goes_north,
+            _ => Self::exits(*symbol).contains(&Direction::North),
+          };
+          inside ^= goes_north;
+          image.set(x, y, if *symbol == 'S' { 3 } else { 2 });
+        } else if inside {
+          image.set(x, y, 1);
+        }
       }
     }
+    Some(image)
+  }
+
+}
 
-    if let Some(c) = Self::go_west(start) {
-      if Self::valid(c, matrix) && vec!['-', 'L', 'F'].contains(&matrix[c.y][c.x]) {
-        possible_starts.push((matrix[c.y][c.x], c))
+impl Day10 {
+  pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+    let mut errors = check_grid_width(lines);
+    let mut starts = Vec::new();
+    for (y, line) in lines.iter().enumerate() {
+      for (x, c) in line.chars().enumerate() {
+        // The tiles the walk knows how to leave, ground and the start
+        match c {
+          'S' => starts.push((y, x)),
+          '.' => (),
+          _ if !Self::exits(c).is_empty() => (),
+          _ => errors.push(InputError::new(y, x, 1, format!("unknown tile '{}'", c))),
+        }
       }
     }
-
-    possible_starts
+    if starts.is_empty() {
+      errors.push(InputError::new(0, 0, 1, "the maze has no starting tile 'S'".to_string()));
+    }
+    for &(y, x) in starts.iter().skip(1) {
+      errors.push(InputError::new(y, x, 1, "the maze has more than one 'S'".to_string()));
+    }
+    if errors.is_empty() {
+      errors.extend(Self::check_loop(lines, starts[0]));
+    }
+    errors
   }
 
-  fn follow_loop(thread_num: u8, pair: Arc<(Mutex<Step>, Condvar)>, start_pos: Coordinate, mut current_pos: Coordinate, mut prev_pos: Coordinate, matrix: Arc<Vec<Vec<char>>>, mut count: i32) {
-    
-    loop {
-      let (lock, cvar) = &*pair;
-      let mut result = lock.lock().unwrap();
-      while (*result).value != 0 {
-        result = cvar.wait(result).unwrap();
+  // Walks the loop from S and reports where it breaks, if it does
+  fn check_loop(lines: &[&str], start: (usize, usize)) -> Option<InputError> {
+    let grid = Grid::from_rows(lines.iter().map(|l| l.chars().collect()).collect())?;
+    let tile = |p: Point| -> char { grid.get(p).copied().unwrap_or('.') };
+    let start = Point::new(start.1 as i64, start.0 as i64);
+    let connected: Vec<Direction> = Direction::ORTHOGONAL.into_iter()
+      .filter(|&d| Self::exits(tile(start.step(d))).contains(&d.reverse()))
+      .collect();
+    if connected.len() != 2 {
+      let message = format!("'S' must connect to exactly 2 pipes, it connects to {}", connected.len());
+      return Some(InputError::new(start.y as usize, start.x as usize, 1, message));
+    }
+    let (mut prev, mut pos) = (start, start.step(connected[0]));
+    while pos != start {
+      let d = *Self::exits(tile(pos)).iter().find(|&&d| pos.step(d) != prev)?;
+      let next = pos.step(d);
+      if next != start && !Self::exits(tile(next)).contains(&d.reverse()) {
+        // Point at the tile that doesn't connect, or at the pipe leading off the grid
+        let at = if grid.contains(next) { next } else { pos };
+        let message = "the loop from 'S' is broken here".to_string();
+        return Some(InputError::new(at.y as usize, at.x as usize, 1, message));
       }
+      (prev, pos) = (pos, next);
+    }
+    None
+  }
 
-      let next_pos = Self::next_position(matrix[current_pos.y][current_pos.x], current_pos, prev_pos);
-      
-      println!("thread {}, next pos - x: {} y: {}", thread_num, next_pos.x, next_pos.y);
+  fn read_grid(path_to_input: &str) -> Option<Grid<char>> {
+    Self::try_read_grid(path_to_input).ok()
+  }
 
-      if next_pos == start_pos {
-        *result = Step{
-          coord: Coordinate {x: next_pos.x, y: next_pos.y},
-          value: -1
-        };
-        cvar.notify_one(); 
-        break;
-      } else {
-        *result = Step{
-          coord: Coordinate {x: next_pos.x, y: next_pos.y},
-          value: count + 1
-        };
-        cvar.notify_one();  
-        count +=1; 
-        prev_pos = current_pos;
-        current_pos = next_pos;
-      }
+  fn try_read_grid(path_to_input: &str) -> Result<Grid<char>, String> {
+    let text = input::read_input(path_to_input).map_err(|e| format!("{}: {}", path_to_input, e))?;
+    if let Some(error) = check_grid_width(&input::lines(&text)).first() {
+      return Err(format!("{}:{}: {}", path_to_input, error.line + 1, error.message));
     }
+    Grid::parse(&text).ok_or_else(|| format!("{}: the rows are not all as long", path_to_input))
   }
 
-  // where is c1 relative to c2?
-  fn position(c1: Coordinate, c2: Coordinate) -> Position {
-    if c1.x + 1 == c2.x && c1.y == c2.y {
-      Position::West
-    } else if c1.x == c2.x + 1 && c1.y == c2.y {
-      Position::East
-    } else if c1.x == c2.x && c1.y + 1 == c2.y {
-      Position::North
-    } else if c1.x == c2.x && c1.y == c2.y + 1 {
-      Position::South
-    } else if c1.x + 1 == c2.x && c1.y + 1 == c2.y {
-      Position::NorthWest
-    } else if c1.x == c2.x + 1 && c1.y + 1 == c2.y {
-      Position::NorthEast
-    } else if c1.x + 1 == c2.x && c1.y == c2.y + 1 {
-      Position::SouthWest
-    } else {
-      Position::SouthEast
+  // Which way each pipe leads
+  fn exits(symbol: char) -> &'static [Direction] {
+    match symbol {
+      '|' => &[Direction::North, Direction::South],
+      '-' => &[Direction::East, Direction::West],
+      'L' => &[Direction::North, Direction::East],
+      'J' => &[Direction::North, Direction::West],
+      '7' => &[Direction::South, Direction::West],
+      'F' => &[Direction::South, Direction::East],
+      _ => &[],
     }
   }
 
-  fn go_east(c: Coordinate) -> Option<Coordinate> {
-    Some(
-      Coordinate{
-        x: c.x + 1,
-        y: c.y
-      }
-    )}
-
-  fn go_west(c: Coordinate) -> Option<Coordinate> {
-    if c.x >= 1 {
-      Some(
-        Coordinate {
-          x: c.x - 1,
-          y: c.y
-        }
-      )
-    } else { 
-      None
-    }
+  fn find_start(matrix: &Grid<char>) -> Point {
+    matrix.find(|&symbol| symbol == 'S').unwrap_or(Point::new(0, 0))
   }
 
-  fn go_north(c: Coordinate) -> Option<Coordinate> {
-    if c.y >= 1 {
-      Some(
-        Coordinate{
-          x: c.x,
-          y: c.y - 1
-      })
-    } else { 
-      None
-    }    
+  fn find_possible_directions_around_start(start: Point, matrix: &Grid<char>) -> Vec::<(char, Point)> {
+    Direction::ORTHOGONAL.into_iter()
+      .map(|d| (d, start.step(d)))
+      .filter_map(|(d, c)| matrix.get(c).map(|&symbol| (d, symbol, c)))
+      .filter(|&(d, symbol, _)| Self::exits(symbol).contains(&d.reverse()))
+      .map(|(_, symbol, c)| (symbol, c))
+      .collect()
   }
 
-  fn go_south(c: Coordinate) -> Option<Coordinate> {
-    Some(Coordinate{
-      x: c.x,
-      y: c.y + 1
+  // The pipes as a graph, two tiles joined when each leads into the other
+  fn pipes(matrix: &Grid<char>) -> Implicit<'_, Point> {
+    Implicit::unweighted(move |&p: &Point| {
+      let exits: Vec<Direction> = match matrix.get(p) {
+        Some('S') => Direction::ORTHOGONAL.to_vec(),
+        Some(&symbol) => Self::exits(symbol).to_vec(),
+        None => Vec::new(),
+      };
+      exits.into_iter()
+        .filter(|&d| match matrix.get(p.step(d)) {
+          Some('S') => true,
+          Some(&next) => Self::exits(next).contains(&d.reverse()),
+          None => false,
+        })
+        .map(|d| p.step(d))
+        .collect()
     })
   }
 
-  fn valid(c: Coordinate, matrix: &Vec<Vec<char>>) -> bool {
-    if c.y >= matrix.len() || c.x >= matrix[0].len() {
-      false
-    } else {
-      true
+  // Every coordinate of the loop through S, walking it once from S
+  fn main_loop(matrix: &Grid<char>) -> Vec<Point> {
+    let start = Self::find_start(matrix);
+    let possible_dirs = Self::find_possible_directions_around_start(start, matrix);
+    let mut main_loop = vec![start];
+    if let Some(&(_, first)) = possible_dirs.first() {
+      main_loop.extend(Self::walk(matrix, start, first));
     }
+    main_loop
   }
 
-  fn next_position(symbol: char, symbol_position: Coordinate, prev_position: Coordinate) -> Coordinate 
-  {
-    let Some(pos) = (match symbol {
-      '-' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::West => Self::go_east(symbol_position),
-          Position::East => Self::go_west(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      '|' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::North => Self::go_south(symbol_position),
-          Position::South => Self::go_north(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      'L' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::North => Self::go_east(symbol_position),
-          Position::East => Self::go_north(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      'J' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::North => Self::go_west(symbol_position),
-          Position::West => Self::go_north(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      '7' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::South => Self::go_west(symbol_position),
-          Position::West => Self::go_south(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      'F' => {
-        match Self::position(prev_position, symbol_position) {
-          Position::South => Self::go_east(symbol_position),
-          Position::East => Self::go_south(symbol_position),
-          _ => panic!("- pipe but positions do not make sense!")
-        }
-      }
-      _ => panic!("Unrecognized symbol!")
-    }) else {
-      panic!("Pipe is taking us out of bounds!!");
-    };
+  // The tiles of the loop from first on, going away from start until it is back there
+  fn walk(matrix: &Grid<char>, start: Point, first: Point) -> Vec<Point> {
+    let mut path = Vec::new();
+    let (mut prev_pos, mut current_pos) = (start, first);
+    while current_pos != start {
+      path.push(current_pos);
+      let next_pos = Self::next_position(matrix, current_pos, prev_pos);
+      prev_pos = current_pos;
+      current_pos = next_pos;
+    }
+    path
+  }
 
+  // Leaves the pipe at symbol_position through the exit we did not come in by
+  fn next_position(matrix: &Grid<char>, symbol_position: Point, prev_position: Point) -> Point {
+    let symbol = matrix[symbol_position];
+    let exits = Self::exits(symbol);
+    if exits.is_empty() {
+      panic!("Unrecognized symbol!");
+    }
+    let Some(came_from) = symbol_position.direction_to(prev_position).filter(|d| exits.contains(d)) else {
+      panic!("{} pipe but positions do not make sense!", symbol);
+    };
+    let Some(&to) = exits.iter().find(|&&d| d != came_from) else {
+      panic!("{} pipe but positions do not make sense!", symbol);
+    };
+    let pos = symbol_position.step(to);
+    if !matrix.contains(pos) {
+      panic!("Pipe is taking us out of bounds!!");
+    }
     pos
   }
 
@@ -382,7 +291,7 @@ mod tests {
 
     #[test]
     fn part1_works_on_sample_input() {
-        let day10 = Day10::new(&"./inputs/day10_test.input".to_string());
+        let day10 = Day10::new("./inputs/day10_test.input");
         let AocRes::Int32(res) = day10.part1() else {
             panic!("Failed to get result from part 1!")
         };
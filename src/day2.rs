@@ -1,9 +1,8 @@
 use crate::aoc::{Aoc, AocRes};
-use regex::Regex;
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use crate::input;
+use crate::check::InputError;
+use crate::explain::Explanation;
+use crate::scan::{self, Pattern, ScanError};
 
 pub struct Day2 {
     path_to_input: String
@@ -13,66 +12,27 @@ const RED_LIMIT: i32 = 12;
 const GREEN_LIMIT: i32 = 13;
 const BLUE_LIMIT: i32 = 14;
 
+const GAME: &str = "Game {id}: {rounds}";
+const DRAW: &str = "{count} {color}";
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+// The game id and, for every round, the count of each color
+type Game = (i64, Vec<Vec<(String, i32)>>);
+
 impl Aoc for Day2 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self{
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
         let mut answer: i64 = 0;
-        if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-            // Consumes the iterator, returns an (Optional) String
-            for line in lines {
-                if let Ok(ip) = line {
-                    // Break via delimiter to separate game from rounds
-                    let parts = ip.split(": ");
-                    let mut v = parts.collect::<VecDeque<&str>>();
-                    let Some(game_id) = v.pop_front() else {
-                        panic!("Line does not contain game xx: format!");
-                    };
-                    let re = Regex::new(r"(\d+)").unwrap();
-                    let Some(caps) = re.captures(game_id) else {
-                        panic!("Line does not contain any numbers!");
-                    };
-                    let id = caps[0].to_string();
-                    // println!("id: {}", id);
-                    let Some(rounds_text) = v.pop_back() else {
-                        panic!("Line does not contain round information!");
-                    };
-                    // Break via delimiter again to separate rounds
-                    let rounds = rounds_text.split("; ");
-                    let mut game_okay = true;
-                    for round in rounds {
-                        // Split by color
-                        let colors = ["red", "green", "blue"];
-                        let mut parsed_round: Vec<(String, i32)> = Vec::<(String, i32)>::new();
-                        for color in colors {
-                            let formatted = format!(r"(\d+)[^\d]+{}", color);
-                            let re = Regex::new(formatted.as_str()).unwrap();
-                            let color_and_num = match re.captures(round) {
-                                Some(num) => {
-                                    // println!("num[1]: {}", num[1].to_string());
-                                    (
-                                        color.to_string(),
-                                        num[1].to_string().parse::<i32>().unwrap(),
-                                    )
-                                }
-                                _ => (color.to_string(), 0),
-                            };
-                            parsed_round.push(color_and_num);
-                        }
-                        if Self::check_round_ok(parsed_round) == false {
-                            game_okay = false;
-                            break;
-                        }
-                    }
-                    if game_okay {
-                        answer = answer + id.parse::<i64>().unwrap();
-                    }
-                }
+        for (id, rounds) in Self::read_games(&self.path_to_input) {
+            let game_okay = rounds.into_iter().all(Self::check_round_ok);
+            if game_okay {
+                answer += id;
             }
         }
 
@@ -83,10 +43,112 @@ impl Aoc for Day2 {
        AocRes::Int64(0)
        // TODO: complete this at some point...
     }
+
+    fn parse(&self) -> Result<(), String> {
+        Self::try_read_games(&self.path_to_input).map(drop)
+    }
+
+    fn explain(&self, part: u32) -> Option<Explanation> {
+        if part != 1 {
+            return None;
+        }
+        let columns = ["game", "max red", "max green", "max blue", "possible", "counted"];
+        let mut explanation = Explanation::new(&columns, self.part1());
+        for (id, rounds) in Self::read_games(&self.path_to_input) {
+            let mut max = [0; 3];
+            for round in &rounds {
+                for (i, (_color, num)) in round.iter().enumerate() {
+                    max[i] = max[i].max(*num);
+                }
+            }
+            let possible = rounds.into_iter().all(Self::check_round_ok);
+            let counted = if possible { id } else { 0 };
+            explanation.push(vec![
+                id.into(),
+                max[0].into(),
+                max[1].into(),
+                max[2].into(),
+                possible.into(),
+                counted.into(),
+            ]);
+        }
+        Some(explanation)
+    }
 }
 
 impl Day2 {
 
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let (game, draw) = (Pattern::new(GAME), Pattern::new(DRAW));
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let error = Self::parse_game(line, &game, &draw).err()?;
+                Some(error.to_input_error(i, line))
+            })
+            .collect()
+    }
+
+    fn read_games(path_to_input: &str) -> Vec<Game> {
+        Self::try_read_games(path_to_input).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_read_games(path_to_input: &str) -> Result<Vec<Game>, String> {
+        let (game, draw) = (Pattern::new(GAME), Pattern::new(DRAW));
+        let lines = input::read_lines(path_to_input).unwrap_or_default();
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                Self::parse_game(line, &game, &draw)
+                    .map_err(|error| format!("{}:{}: {}", path_to_input, i + 1, error))
+            })
+            .collect()
+    }
+
+    fn parse_game(
+        line: &str,
+        game: &Pattern,
+        draw: &Pattern,
+    ) -> Result<Game, ScanError> {
+        let fields = game.matches(line)?;
+        let id = fields.get("id")?;
+        let mut parsed_rounds = Vec::new();
+        for (round_offset, round) in fields.split("rounds", ";") {
+            // Colors that are not drawn count as 0
+            let mut counts = [0; 3];
+            for (draw_offset, text) in scan::split(round, ",") {
+                let (color, count) = Self::parse_draw(draw, text)
+                    .map_err(|e| e.shifted(round_offset + draw_offset))?;
+                counts[color] = count;
+            }
+            parsed_rounds.push(
+                COLORS
+                    .iter()
+                    .zip(counts)
+                    .map(|(color, count)| (color.to_string(), count))
+                    .collect(),
+            );
+        }
+        Ok((id, parsed_rounds))
+    }
+
+    // One draw like '3 blue' as the index of its color and the count
+    fn parse_draw(draw: &Pattern, text: &str) -> Result<(usize, i32), ScanError> {
+        let fields = draw.matches(text)?;
+        let count = fields.get("count")?;
+        let color = fields.str("color");
+        let Some(index) = COLORS.iter().position(|&c| c == color) else {
+            return Err(ScanError::new(
+                fields.offset("color"),
+                color.len(),
+                format!("expected red, green or blue, found '{}'", color),
+            ));
+        };
+        Ok((index, count))
+    }
+
     fn check_round_ok(parsed_round: Vec<(String, i32)>) -> bool {
         for (color, num) in parsed_round {
             match color.as_str() {
@@ -112,13 +174,4 @@ impl Day2 {
         true
     }
 
-    // The output is wrapped in a Result to allow matching on errors
-    // Returns an Iterator to the Reader of the lines of the file.
-    fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where
-        P: AsRef<Path>,
-    {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
-    }
 }
@@ -1,7 +1,9 @@
 use crate::aoc::{Aoc, AocRes};
-use regex::Regex;
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::input;
+use crate::check::{check_grid_width, InputError};
+use crate::explain::Explanation;
+use crate::grid::{Grid, Point};
+use crate::image::CellImage;
 use std::path::Path;
 
 pub struct Day3 {
@@ -10,107 +12,142 @@ pub struct Day3 {
 
 impl Aoc for Day3 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self {
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
         let mut answer: i32 = 0;
-        let mat = Self::read_into_matrix(&self.path_to_input);
-        let m = mat.len();
-        let n = mat[0].len();
-        // println!("rows: {}, cols: {}", m, n);
-        // Assumption: rows are of consistent length
-        for i in 0..m {
-            let row = mat[i].clone();
-            let next_row = if i >= (m - 1) {
-                vec!['.'; n]
-            } else {
-                mat[i + 1].clone()
-            };
-            let prev_row = if i <= 0 {
-                vec!['.'; n]
-            } else {
-                mat[i - 1].clone()
-            };
-            // match all numbers and get their positions
-            let s: String = row.clone().into_iter().collect();
-            let re = Regex::new(r"\d+").unwrap();
-            for found_match in re.find_iter(s.as_str()) {
-                let mut sidx = found_match.start();
-                let mut eidx = found_match.end();
-                // look around for a symbol
-                // it would be more efficient to match symbols first then look around for numbers, but harder to do
-                sidx = if ((sidx as i32) - 1) < 0 {
-                    sidx
-                } else {
-                    sidx - 1
-                };
-                eidx = if eidx + 1 > (n - 1) { eidx } else { eidx + 1 };
-                if prev_row[sidx..eidx].iter().any(|el| {
-                    !matches!(
-                        el,
-                        '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '0' | '.'
-                    )
-                }) || row[sidx..eidx].iter().any(|el| {
-                    !matches!(
-                        el,
-                        '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '0' | '.'
-                    )
-                }) || next_row[sidx..eidx].iter().any(|el| {
-                    !matches!(
-                        el,
-                        '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '0' | '.'
-                    )
-                }) {
-                    let num = found_match.as_str().to_string().parse::<i32>().unwrap();
-                    // if i == 0 || i == (m-1) {
-                    //   println!("{}", num);
-                    // }
-                    answer = answer + num;
-                }
+        let grid = Self::read_grid(&self.path_to_input);
+        for (_row, _col, num, counted) in Self::find_numbers(&grid) {
+            if counted {
+                answer += num;
             }
         }
         AocRes::Int32(answer)
     }
 
     fn part2(&self) -> AocRes {
-        let mut answer: i32 = 0;
+        let answer: i32 = 0;
         AocRes::Int32(answer)
     }
 
+    fn parse(&self) -> Result<(), String> {
+        Self::try_read_grid(&self.path_to_input).map(drop)
+    }
+
+    fn explain(&self, part: u32) -> Option<Explanation> {
+        if part != 1 {
+            return None;
+        }
+        let columns = ["row", "column", "number", "next to a symbol"];
+        let mut explanation = Explanation::new(&columns, self.part1());
+        let grid = Self::read_grid(&self.path_to_input);
+        for (row, col, num, counted) in Self::find_numbers(&grid) {
+            explanation.push(vec![(row + 1).into(), (col + 1).into(), num.into(), counted.into()]);
+        }
+        Some(explanation)
+    }
 
+    fn image(&self) -> Option<CellImage> {
+        let grid = Self::read_grid(&self.path_to_input);
+        let legend = [
+            ("empty", [240, 240, 240]),
+            ("symbol", [200, 40, 40]),
+            ("counted number", [40, 160, 60]),
+            ("ignored number", [150, 150, 150]),
+        ];
+        let mut image = CellImage::new(grid.width(), grid.height(), &legend);
+        for (p, c) in grid.iter() {
+            let (x, y) = (p.x as usize, p.y as usize);
+            match c {
+                '.' => (),
+                '0'..='9' => image.set(x, y, 3),
+                _ => image.set(x, y, 1),
+            }
+        }
+        for (row, col, _num, counted) in Self::find_numbers(&grid) {
+            let digits = grid.row(row)[col..].iter().take_while(|c| c.is_ascii_digit()).count();
+            for x in col..col + digits {
+                if counted {
+                    image.set(x, row, 2);
+                }
+            }
+        }
+        Some(image)
+    }
 }
 
 impl Day3 {
 
-    fn read_into_matrix<P>(path_to_input: P) -> Vec<Vec<char>>
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let mut errors = check_grid_width(lines);
+        for (i, line) in lines.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if c.is_whitespace() || c.is_control() {
+                    errors.push(InputError::new(
+                        i,
+                        column,
+                        1,
+                        format!("unexpected {:?} in the schematic", c),
+                    ));
+                }
+            }
+        }
+        errors
+    }
+
+    // Every number in the schematic with its row and column, and whether a
+    // symbol next to it makes it count
+    fn find_numbers(grid: &Grid<char>) -> Vec<(usize, usize, i32, bool)> {
+        let mut found = Vec::new();
+        let is_symbol = |c: &char| !c.is_ascii_digit() && *c != '.';
+        for (i, row) in grid.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x].is_ascii_digit() {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < row.len() && row[x].is_ascii_digit() {
+                    x += 1;
+                }
+                let num = row[start..x].iter().collect::<String>().parse::<i32>().unwrap();
+                // look around for a symbol, the box around the number is clipped to the grid
+                let y = i as i64;
+                let top_left = Point::new(start as i64 - 1, y - 1);
+                let bottom_right = Point::new(x as i64, y + 1);
+                let counted = grid.region(top_left, bottom_right).any(|(_, c)| is_symbol(c));
+                found.push((i, start, num, counted));
+            }
+        }
+        found
+    }
+
+    fn read_grid<P>(path_to_input: P) -> Grid<char>
     where
         P: AsRef<Path>,
     {
-        if let Ok(lines) = Self::read_lines(path_to_input) {
-            let mut mat: Vec<Vec<char>> = Vec::new();
-            for line in lines {
-                if let Ok(l) = line {
-                    let chars: Vec<_> = l.chars().collect();
-                    mat.push(chars);
-                }
-            }
-            mat
-        } else {
+        let Ok(grid) = Self::try_read_grid(path_to_input) else {
             panic!("not able to read file!")
-        }
+        };
+        grid
     }
 
-    // The output is wrapped in a Result to allow matching on errors
-    // Returns an Iterator to the Reader of the lines of the file.
-    fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+    fn try_read_grid<P>(path_to_input: P) -> Result<Grid<char>, String>
     where
         P: AsRef<Path>,
     {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
+        let path = path_to_input.as_ref();
+        let text = input::read_input(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // The checker's own rule, so that the two agree on what a grid is
+        if let Some(error) = check_grid_width(&input::lines(&text)).first() {
+            return Err(format!("{}:{}: {}", path.display(), error.line + 1, error.message));
+        }
+        Grid::parse(&text).ok_or_else(|| format!("{}: the rows are not all as long", path.display()))
     }
+
 }
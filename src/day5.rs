@@ -1,10 +1,20 @@
 use crate::aoc::{Aoc, AocRes};
+use crate::input;
+use crate::check::InputError;
+use crate::interval::{Interval, RangeSet};
+use crate::parallel;
+use crate::scan::{Pattern, ScanError};
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{self, BufRead};
 use std::path::Path;
-use std::sync::mpsc::channel;
-use std::thread::spawn;
+
+// A source range and the destination its first value maps to
+type MapRange = (Interval<u64>, u64);
+// The line of a map's header, its name and its ranges
+type NamedMap<'t> = (usize, &'t str, Vec<MapRange>);
+
+const SEEDS: &str = "seeds: {seeds}";
+const HEADER: &str = "{name} map:";
+const RANGE: &str = "{dest} {source} {length}";
 
 pub struct Day5 {
     path_to_input: String
@@ -12,158 +22,256 @@ pub struct Day5 {
 
 impl Aoc for Day5 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self {
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
-        let mut maps = VecDeque::<VecDeque<(u64, u64, u64)>>::new();
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "seed-to-soil"));
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "soil-to-fertilizer"));
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "fertilizer-to-water"));
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "water-to-light"));
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "light-to-temperature"));
-        maps.push_back(Self::parse_ranges(
-            &self.path_to_input,
-            "temperature-to-humidity",
-        ));
-        maps.push_back(Self::parse_ranges(&self.path_to_input, "humidity-to-location"));
-
-        let (sender, receiver) = channel();
-        let mut handle_vec = vec![];
+        let maps = Self::maps(&self.path_to_input);
+        let seeds = Self::get_seeds(&self.path_to_input);
+        let closest = parallel::map_reduce(seeds, |seed| Self::apply_chain(seed, &maps), u64::min)
+            .unwrap_or_else(|e| e.raise());
+        let Some(answer) = closest else {
+            panic!("No results!")
+        };
+        AocRes::UInt64(answer)
+    }
 
+    // The seeds come in pairs of a start and a length, far too many to map one
+    // by one, so whole ranges of them go through the maps instead
+    fn part2(&self) -> AocRes {
         let seeds = Self::get_seeds(&self.path_to_input);
-        let mut answers = Vec::<u64>::new();
-
-        for seed in seeds {
-            let sender_clone = sender.clone();
-            let maps_clone = maps.clone();
-            let handle = spawn(move || {
-                // make a handle
-                let loc = Self::apply_chain(seed, &maps_clone);
-                sender_clone.send(loc).unwrap(); // use the sender_clone to send the work to the receiver
-            });
-            handle_vec.push(handle);
+        let seeds: Vec<u64> = seeds.into();
+        let mut ranges: RangeSet<u64> = seeds
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [start, len] => Some(Interval::new(*start, start.saturating_add(*len))),
+                _ => None,
+            })
+            .collect();
+        for map in Self::maps(&self.path_to_input) {
+            ranges = Self::map_ranges(&ranges, &map);
         }
+        // Without a single complete pair there are no seeds to plant
+        AocRes::UInt64(ranges.min().unwrap_or(0))
+    }
+
+    // 2: part 2 maps whole seed ranges and no longer answers 0
+    fn version(&self) -> &str {
+        "2"
+    }
+
+    fn parse(&self) -> Result<(), String> {
+        Self::try_get_seeds(&self.path_to_input)?;
+        Self::try_maps(&self.path_to_input).map(drop)
+    }
 
-        for handle in handle_vec {
-            // stop until the threads are done
-            handle.join().unwrap();
+    // Every seed with what it maps to in each category on the way to its location
+    fn snapshot(&self) -> Option<String> {
+        let maps = Self::maps(&self.path_to_input);
+        let categories = Self::MAP_NAMES.iter().filter_map(|name| name.split("-to-").nth(1));
+        let header: Vec<&str> = std::iter::once("seed").chain(categories).collect();
+        let mut lines = vec![header.join(" -> ")];
+        for seed in Self::get_seeds(&self.path_to_input) {
+            let chain: Vec<String> = std::iter::once(seed)
+                .chain(maps.iter().scan(seed, |val, map| {
+                    *val = Self::find_mapped_value(*val, map);
+                    Some(*val)
+                }))
+                .map(|val| val.to_string())
+                .collect();
+            lines.push(chain.join(" -> "));
         }
+        Some(lines.join("\n") + "\n")
+    }
+}
 
-        while let Ok(res) = receiver.try_recv() {
-            // println!("thread res: {}", res);
-            answers.push(res); // push the results from receiver.recv() into the vec
+impl Day5 {
+
+    pub const MAP_NAMES: [&str; 7] = [
+        "seed-to-soil",
+        "soil-to-fertilizer",
+        "fertilizer-to-water",
+        "water-to-light",
+        "light-to-temperature",
+        "temperature-to-humidity",
+        "humidity-to-location",
+    ];
+
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let mut errors = Vec::new();
+        let first = lines.first().unwrap_or(&"");
+        if let Err(error) = Self::parse_seeds(&Pattern::new(SEEDS), first) {
+            errors.push(error.to_input_error(0, first));
+        }
+        let (maps, bad_lines) = Self::read_maps(lines);
+        for (i, error) in bad_lines {
+            errors.push(error.to_input_error(i, lines[i]));
+        }
+        let mut seen = Vec::<&str>::new();
+        for (i, name, _) in maps {
+            if seen.contains(&name) {
+                errors.push(InputError::whole_line(
+                    i,
+                    lines[i],
+                    format!("'{}' map appears twice", name),
+                ));
+            }
+            seen.push(name);
+        }
+        let last = lines.len().saturating_sub(1);
+        let end = lines.last().map(|l| l.chars().count()).unwrap_or(0);
+        for name in Self::MAP_NAMES {
+            if !seen.contains(&name) {
+                errors.push(InputError::new(last, end, 1, format!("missing the '{}' map", name)));
+            }
+        }
+        errors
+    }
+
+    // Every map, in the order a seed goes through them
+    fn maps(path_to_input: &str) -> VecDeque<Vec<MapRange>> {
+        Self::try_maps(path_to_input).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    // A map the input leaves out maps every value to itself, of one that appears
+    // twice the first one counts
+    fn try_maps(path_to_input: &str) -> Result<VecDeque<Vec<MapRange>>, String> {
+        let text = input::read_input(path_to_input).map_err(|e| format!("{}: {}", path_to_input, e))?;
+        let (maps, bad_lines) = Self::read_maps(&input::lines(&text));
+        if let Some((i, error)) = bad_lines.first() {
+            return Err(format!("{}:{}: {}", path_to_input, i + 1, error.message));
         }
+        let mut ordered = vec![None; Self::MAP_NAMES.len()];
+        for (_, name, ranges) in maps {
+            let Some(index) = Self::MAP_NAMES.iter().position(|&n| n == name) else {
+                continue;
+            };
+            ordered[index].get_or_insert(ranges);
+        }
+        Ok(ordered.into_iter().map(Option::unwrap_or_default).collect())
+    }
 
-        let Some(mut answer) = answers.pop() else {
-            panic!("No results!")
-        };
-        for a in answers {
-            if a < answer {
-                answer = a;
+    // Every map after the seeds with the line of its header, and every line that
+    // is neither a header nor a range inside a map
+    fn read_maps<'t>(lines: &[&'t str]) -> (Vec<NamedMap<'t>>, Vec<(usize, ScanError)>) {
+        let (header, range) = (Pattern::new(HEADER), Pattern::new(RANGE));
+        let mut maps = Vec::<NamedMap>::new();
+        let mut bad_lines = Vec::new();
+        let mut in_map = false;
+        // The map the ranges go into, none under a header that is not one
+        let mut current = None;
+        for (i, line) in lines.iter().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                in_map = false;
+            } else if !in_map {
+                current = match Self::parse_header(&header, line) {
+                    Ok(name) => {
+                        maps.push((i, name, Vec::new()));
+                        Some(maps.len() - 1)
+                    }
+                    Err(error) => {
+                        bad_lines.push((i, error));
+                        None
+                    }
+                };
+                in_map = true;
+            } else {
+                match Self::parse_range(&range, line) {
+                    Ok(parsed) => {
+                        if let Some(map) = current {
+                            maps[map].2.push(parsed);
+                        }
+                    }
+                    Err(error) => bad_lines.push((i, error)),
+                }
             }
         }
+        (maps, bad_lines)
+    }
 
-        AocRes::UInt64(answer)
+    fn parse_seeds(seeds: &Pattern, line: &str) -> Result<Vec<u64>, ScanError> {
+        seeds.matches(line)?.list("seeds")
     }
 
-    fn part2(&self) -> AocRes {
-        let mut answer: u64 = 0;
-        AocRes::UInt64(answer)
+    fn parse_header<'t>(header: &Pattern, line: &'t str) -> Result<&'t str, ScanError> {
+        let Ok(fields) = header.matches(line) else {
+            let message = "expected a header like 'seed-to-soil map:'".to_string();
+            return Err(ScanError::new(0, line.len(), message));
+        };
+        let name = fields.str("name");
+        if !Self::MAP_NAMES.contains(&name) {
+            let message = format!("unknown map '{}'", name);
+            return Err(ScanError::new(fields.offset("name"), name.len(), message));
+        }
+        Ok(name)
     }
-}
 
-impl Day5 {
+    // One range like '50 98 2' as the source range and where it starts in the destination
+    fn parse_range(range: &Pattern, line: &str) -> Result<MapRange, ScanError> {
+        let Ok(fields) = range.matches(line) else {
+            let message = "expected destination start, source start and range length".to_string();
+            return Err(ScanError::new(0, line.len(), message));
+        };
+        let (dest, source, length): (u64, u64, u64) =
+            (fields.get("dest")?, fields.get("source")?, fields.get("length")?);
+        Ok((Interval::new(source, source.saturating_add(length)), dest))
+    }
 
-    fn apply_chain(seed: u64, maps: &VecDeque<VecDeque<(u64, u64, u64)>>) -> u64 {
+    fn apply_chain(seed: u64, maps: &VecDeque<Vec<MapRange>>) -> u64 {
         maps.iter()
-            .fold(seed, |acc, map| Self::find_mapped_value(acc, map))
-    }
-
-    fn find_mapped_value(val1: u64, map: &VecDeque<(u64, u64, u64)>) -> u64 {
-        let ret = map
-            .iter()
-            .fold(val1, |acc, (val1_start, range, val2_start)| {
-                if (val1 >= *val1_start) && (val1 <= (val1_start + range)) {
-                    let diff = val1 - val1_start;
-                    val2_start + diff
-                } else {
-                    acc
-                }
-            });
-        // println!("{ret}");
-        ret
+            .fold(seed, |val, map| Self::find_mapped_value(val, map))
     }
 
-    fn get_seeds<P>(filename: &P) -> VecDeque<u64>
-    where
-        P: AsRef<Path>,
-    {
-        let file = File::open(filename).unwrap();
-        let mut buffer = io::BufReader::new(file);
-        let mut first_line = String::new();
-        let _ = buffer.read_line(&mut first_line);
-        let Some(seeds) = first_line
-            .split(": ")
-            .collect::<VecDeque<&str>>()
-            .pop_back()
-        else {
-            panic!("No seeds!");
-        };
-        let seeds = seeds.split(" ").collect::<VecDeque<&str>>();
-        seeds
-            .into_iter()
-            .map(|s| {
-                let f = s.to_string().chars().fold("".to_string(), |acc, c| {
-                    if "1234567890".contains(c) {
-                        acc + &c.to_string()
-                    } else {
-                        acc
-                    }
-                });
-                f.parse::<u64>().unwrap()
+    fn find_mapped_value(val1: u64, map: &[MapRange]) -> u64 {
+        map.iter()
+            .find(|(source, _)| source.contains(val1))
+            .map_or(val1, |(source, dest)| {
+                let Some(mapped) = dest.checked_add(val1 - source.start) else {
+                    panic!("Mapping {} to {} overflows!", val1, dest);
+                };
+                mapped
             })
-            .collect()
     }
 
-    // parses into a hashmap
-    fn parse_ranges<P>(path_to_input: &P, map_name: &str) -> VecDeque<(u64, u64, u64)>
+    // Values inside a source range move to its destination, all others stay as they are
+    fn map_ranges(ranges: &RangeSet<u64>, map: &[MapRange]) -> RangeSet<u64> {
+        let mut unmapped = ranges.clone();
+        let mut mapped = RangeSet::new();
+        for (source, dest) in map {
+            let source_set: RangeSet<u64> = std::iter::once(*source).collect();
+            let inside = unmapped.intersection(&source_set);
+            let Some(moved) = inside.shift(source.start, *dest) else {
+                panic!("Mapping {:?} to {} overflows!", source, dest);
+            };
+            mapped = mapped.union(&moved);
+            unmapped = unmapped.difference(&source_set);
+        }
+        mapped.union(&unmapped)
+    }
+
+    fn get_seeds<P>(filename: &P) -> VecDeque<u64>
     where
         P: AsRef<Path>,
     {
-        let mut bins = VecDeque::<(u64, u64, u64)>::new();
-        let mut start_parsing = false;
-        if let Ok(lines) = Self::read_lines(path_to_input) {
-            for line in lines {
-                if let Ok(l) = line {
-                    if l.contains(map_name) {
-                        start_parsing = true;
-                        continue;
-                    } else if l == "".to_string() && start_parsing == true {
-                        break;
-                    }
-                    if start_parsing == true {
-                        let numbers = l.split(" ").collect::<VecDeque<&str>>();
-                        // println!("{}, {}, {}", numbers[0], numbers[1], numbers[2]);
-                        let dest = numbers[0].to_string().parse::<u64>().unwrap();
-                        let source = numbers[1].to_string().parse::<u64>().unwrap();
-                        let range = numbers[2].to_string().parse::<u64>().unwrap();
-                        bins.push_back((source, range, dest));
-                    }
-                }
-            }
-        }
-        bins
+        Self::try_get_seeds(filename).unwrap_or_else(|error| panic!("{}", error))
     }
 
-    fn read_lines<P>(filename: &P) -> io::Result<io::Lines<io::BufReader<File>>>
+    fn try_get_seeds<P>(filename: &P) -> Result<VecDeque<u64>, String>
     where
         P: AsRef<Path>,
     {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
+        let path = filename.as_ref();
+        let lines = input::read_lines(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let Some(first) = lines.first() else {
+            return Err("No seeds!".to_string());
+        };
+        let seeds = Self::parse_seeds(&Pattern::new(SEEDS), first)
+            .map_err(|error| format!("{}:1: {}", path.display(), error.message))?;
+        Ok(seeds.into())
     }
+
+
 }
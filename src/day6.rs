@@ -13,99 +13,181 @@
 // |-------------> c
 
 use crate::aoc::{Aoc, AocRes};
-use regex::Regex;
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::input;
+use crate::check::InputError;
+use crate::numeric::Num;
+use crate::progress::Progress;
+use crate::scan::{Pattern, ScanError};
+use crate::variants::Variant;
+
+const LABELS: [&str; 2] = ["Time:", "Distance:"];
 
 pub struct Day6 {
     path_to_input: String
 }
 
 impl Aoc for Day6 {
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self {
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
-        let file = File::open(&self.path_to_input).unwrap();
-        let mut buffer = io::BufReader::new(file);
-        let mut first_line = String::new();
-        let _ = buffer.read_line(&mut first_line);
-
-        let re = Regex::new(r"\d+").unwrap();
-        let mut total_times = Vec::<i32>::new();
-        for time_match in re.find_iter(first_line.as_str()) {
-            total_times.push(time_match.as_str().to_string().parse::<i32>().unwrap());
-        }
-
-        let mut second_line = String::new();
-        let _ = buffer.read_line(&mut second_line);
-
-        let mut distances_to_beat = Vec::<i32>::new();
-        for dist_match in re.find_iter(second_line.as_str()) {
-            distances_to_beat.push(dist_match.as_str().to_string().parse::<i32>().unwrap());
-        }
+        let (total_times, distances_to_beat) = self.races();
+        let total_times = total_times.into_iter().map(Num::<i32>::from_int);
+        let distances_to_beat = distances_to_beat.into_iter().map(Num::<i32>::from_int);
 
         let times_and_distances = std::iter::zip(total_times, distances_to_beat);
-        let mut answer = 1;
+        let mut answer = Num(1);
         for (total_time, dist) in times_and_distances {
-            let mut winning_combinations = 0;
-            for c in 0..total_time {
-                let d = -1 * c * c + c * total_time;
+            let mut winning_combinations = Num(0);
+            for c in (0..total_time.get()).map(Num) {
+                let d = c * total_time - c * c;
                 if d > dist {
-                    winning_combinations = winning_combinations + 1;
+                    winning_combinations += Num(1);
                 }
             }
             // println!("total_time: {total_time}, dist to beat: {dist} winning_combinations: {winning_combinations}");
-            answer = answer * winning_combinations;
+            answer *= winning_combinations;
         }
 
-        AocRes::Int32(answer)
+        AocRes::Int32(answer.get())
     }
 
     fn part2(&self) -> AocRes {
-        let file = File::open(&self.path_to_input).unwrap();
-        let mut buffer = io::BufReader::new(file);
-        let mut first_line = String::new();
-        let _ = buffer.read_line(&mut first_line);
-
-        let re = Regex::new(r"\d+").unwrap();
-        let mut total_times = Vec::<String>::new();
-        for time_match in re.find_iter(first_line.as_str()) {
-            total_times.push(time_match.as_str().to_string());
-        }
+        let (total_time, distance_to_beat) = self.long_race();
+        AocRes::Int32(Self::count_each_charge_time(total_time, distance_to_beat))
+    }
 
-        let mut second_line = String::new();
-        let _ = buffer.read_line(&mut second_line);
+    fn parse(&self) -> Result<(), String> {
+        self.try_long_race().map(drop)
+    }
 
-        let mut distances_to_beat = Vec::<String>::new();
-        for dist_match in re.find_iter(second_line.as_str()) {
-            distances_to_beat.push(dist_match.as_str().to_string());
+    fn variants(&self, part: u32) -> Vec<Variant<'_>> {
+        if part != 2 {
+            return Vec::new();
         }
+        vec![
+            Variant::new("count every charge time", || self.part2()),
+            Variant::new("solve the quadratic", || {
+                let (total_time, distance_to_beat) = self.long_race();
+                AocRes::Int32(Self::solve_quadratic(total_time, distance_to_beat))
+            }),
+        ]
+    }
+}
+
+impl Day6 {
+    // Part 2 reads each line as a single number with the spaces taken out
+    fn long_race(&self) -> (i64, i64) {
+        self.try_long_race().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_long_race(&self) -> Result<(i64, i64), String> {
+        let path = &self.path_to_input;
+        let (times, distances) = self.try_races()?;
+        let number = |i: usize, numbers: Vec<u64>| -> Result<i64, String> {
+            let digits: String = numbers.iter().map(u64::to_string).collect();
+            digits
+                .parse::<i64>()
+                .map_err(|e| format!("{}:{}: '{}' as one number: {}", path, i + 1, digits, e))
+        };
+        Ok((number(0, times)?, number(1, distances)?))
+    }
+
+    // The times and the distances to beat of every race
+    fn races(&self) -> (Vec<u64>, Vec<u64>) {
+        self.try_races().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_races(&self) -> Result<(Vec<u64>, Vec<u64>), String> {
+        let path = &self.path_to_input;
+        let lines = input::read_lines(path).map_err(|e| format!("{}: {}", path, e))?;
+        let numbers = |i: usize| -> Result<Vec<u64>, String> {
+            let line = lines.get(i).map_or("", |l| l.as_str());
+            Self::parse_numbers(LABELS[i], line)
+                .map_err(|error| format!("{}:{}: {}", path, i + 1, error.message))
+        };
+        Ok((numbers(0)?, numbers(1)?))
+    }
 
-        let total_time = total_times
-            .iter()
-            .fold("".to_string(), |acc, el| acc + el)
-            .parse::<i64>()
-            .unwrap();
-        let distance_to_beat = distances_to_beat
-            .iter()
-            .fold("".to_string(), |acc, el| acc + el)
-            .parse::<i64>()
-            .unwrap();
-
-        let mut winning_combinations = 0;
+    // The numbers on a line like 'Time:      7  15   30'
+    fn parse_numbers(label: &str, line: &str) -> Result<Vec<u64>, ScanError> {
+        Pattern::new(&format!("{} {{numbers}}", label)).matches(line)?.list("numbers")
+    }
+
+    fn count_each_charge_time(total_time: i64, distance_to_beat: i64) -> i32 {
+        let progress = Progress::current();
+        progress.set_total(total_time as u64);
+        let mut winning_combinations = Num(0);
         for c in 0..total_time {
-            let d = -1 * c * c + c * total_time;
-            if d > distance_to_beat {
-                winning_combinations = winning_combinations + 1;
+            // Reporting every single charge time would cost more than the loop body
+            if c % (1 << 16) == 0 {
+                progress.set(c as u64);
+            }
+            let d = Num(c) * Num(total_time - c);
+            if d > Num(distance_to_beat) {
+                winning_combinations += Num(1);
             }
         }
-        //println!("total_time: {total_time}, dist to beat: {distance_to_beat} winning_combinations: {winning_combinations}");
+        winning_combinations.get()
+    }
 
-        AocRes::Int32(winning_combinations)
+    // Charge times c with c * (T - c) > D lie strictly between the roots of
+    // c^2 - T*c + D = 0; the float roots are nudged to the exact integers
+    fn solve_quadratic(total_time: i64, distance_to_beat: i64) -> i32 {
+        let wins = |c: i64| c * (total_time - c) > distance_to_beat;
+        let discriminant = Num(total_time) * Num(total_time) - Num(4) * Num(distance_to_beat);
+        let discriminant = discriminant.get() as f64;
+        if discriminant < 0.0 {
+            return 0;
+        }
+        let root = discriminant.sqrt();
+        let mut low = ((total_time as f64 - root) / 2.0).floor().max(0.0) as i64;
+        // The winning charge times are symmetric around T / 2, so the first
+        // one is never past it
+        while 2 * low <= total_time && !wins(low) {
+            low += 1;
+        }
+        while low > 0 && wins(low - 1) {
+            low -= 1;
+        }
+        if 2 * low > total_time {
+            return 0;
+        }
+        Num::<i32>::from_int(total_time - 2 * low + 1).get()
     }
-}
 
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let mut errors = Vec::new();
+        let mut counts = Vec::new();
+        for (i, label) in LABELS.iter().enumerate() {
+            let line = lines.get(i).unwrap_or(&"");
+            match Self::parse_numbers(label, line) {
+                Ok(numbers) => counts.push(numbers.len()),
+                Err(error) => errors.push(error.to_input_error(i, line)),
+            }
+        }
+        if let [times, distances] = counts[..] {
+            if times != distances {
+                errors.push(InputError::whole_line(
+                    1,
+                    lines[1],
+                    format!("{} times but {} distances", times, distances),
+                ));
+            }
+        }
+        for (i, line) in lines.iter().enumerate().skip(2) {
+            if !line.trim().is_empty() {
+                errors.push(InputError::whole_line(
+                    i,
+                    line,
+                    "unexpected line after the distances".to_string(),
+                ));
+            }
+        }
+        errors
+    }
+
+}
@@ -1,8 +1,11 @@
 use crate::aoc::{Aoc, AocRes};
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+use crate::input;
+use crate::check::InputError;
+use crate::explain::Explanation;
+use crate::runner::solve_part;
+use crate::scan::{Pattern, ScanError};
+use crate::variants::Variant;
+use std::collections::HashMap;
 
 // Algo:
 // Parse each 5 card combo
@@ -11,12 +14,14 @@ use std::path::Path;
 // - Parse left to right, keep track of largest group in a priority queue
 // - This will find 5 of a kind, 4 of a kind, 3 of a kind, full house, two pair, one pair combos and high card
 
+const HAND: &str = "{hand} {bid}";
+
 pub struct Day7 {
     path_to_input: String
 }
 
-#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
-enum HandTypes {
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
+pub enum HandTypes {
     Unknown,
     FiveOfAKind(u8),
     FourOfAKind(u8, u8),
@@ -29,115 +34,197 @@ enum HandTypes {
 
 impl Aoc for Day7 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self {
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
-        let mut total_sum = 0;
-        let mut hands = Vec::<(HandTypes, i32, String)>::new();
+        let hands = Self::sorted_hands(
+            &self.path_to_input,
+            Self::categorize_type_part1,
+            Self::char_to_val_part1,
+        );
+        AocRes::Int32(Self::total_winnings(&hands))
+    }
 
-        if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-            // Consumes the iterator, returns an (Optional) String
-            for line in lines {
-                if let Ok(l) = line {
-                    let mut v = l.split(" ").collect::<VecDeque<&str>>();
-                    let Some(bid) = v.pop_back() else {
-                        panic!("Could not parse bid!");
-                    };
-                    let bid = bid.to_string().parse::<i32>().unwrap();
-                    let Some(hand) = v.pop_front() else {
-                        panic!("Could not parse hand!");
-                    };
-                    let (hand_type, sorted) = Self::categorize_type_part1(hand);
-                    // hands.push((hand_type, bid, sorted));
-                    hands.push((hand_type, bid, hand.to_string()));
-                }
-            }
+    fn part2(&self) -> AocRes {
+        let hands = Self::sorted_hands(
+            &self.path_to_input,
+            Self::categorize_type_part2,
+            Self::char_to_val_part2,
+        );
+        AocRes::Int32(Self::total_winnings(&hands))
+    }
+
+    fn parse(&self) -> Result<(), String> {
+        Self::try_read_hands(&self.path_to_input, Self::categorize_type_part1)?;
+        Self::try_read_hands(&self.path_to_input, Self::categorize_type_part2).map(drop)
+    }
+
+    fn explain(&self, part: u32) -> Option<Explanation> {
+        let hands = match part {
+            1 => Self::sorted_hands(
+                &self.path_to_input,
+                Self::categorize_type_part1,
+                Self::char_to_val_part1,
+            ),
+            2 => Self::sorted_hands(
+                &self.path_to_input,
+                Self::categorize_type_part2,
+                Self::char_to_val_part2,
+            ),
+            _ => return None,
+        };
+        let answer = if part == 1 { self.part1() } else { self.part2() };
+        let columns = ["rank", "hand", "type", "bid", "rank x bid"];
+        let mut explanation = Explanation::new(&columns, answer);
+        for (rank, (hand_type, bid, hand)) in (1..).zip(hands) {
+            let type_name = format!("{:?}", hand_type);
+            let type_name = type_name.split('(').next().unwrap_or_default().to_string();
+            explanation.push(vec![
+                rank.into(),
+                hand.into(),
+                type_name.into(),
+                bid.into(),
+                (rank * bid).into(),
+            ]);
         }
+        Some(explanation)
+    }
 
-        //hands.sort_unstable_by_key(|h| Self::hand_type_to_comparator(h.0));
-        hands.sort_unstable_by_key(|h| {
-            let v = h.2.chars().collect::<Vec<char>>();
-            let (c1, c2, c3, c4, c5) = (v[0], v[1], v[2], v[3], v[4]);
-            (
-                Self::hand_type_to_val(h.0),
-                Self::char_to_val_part1(&c1),
-                Self::char_to_val_part1(&c2),
-                Self::char_to_val_part1(&c3),
-                Self::char_to_val_part1(&c4),
-                Self::char_to_val_part1(&c5),
-            )
-        });
-        // for h in &hands {
-        //     println!("hand: {}, bid: {}", h.2, h.1);
-        // }
-        let (total_ranks, total_sum) = hands
+    fn variants(&self, part: u32) -> Vec<Variant<'_>> {
+        if part != 1 && part != 2 {
+            return Vec::new();
+        }
+        let char_to_val: fn(&char) -> u8 = match part {
+            1 => Self::char_to_val_part1,
+            _ => Self::char_to_val_part2,
+        };
+        vec![
+            Variant::new("card order", move || solve_part(self, part)),
+            // Ranks hands by the sizes of their groups of equal cards instead of
+            // naming their type, then by their cards from the first one on
+            Variant::new("group sizes", move || {
+                let mut hands = Self::read_hands(&self.path_to_input, Self::categorize_type_part1);
+                hands.sort_by_cached_key(|(_, _, hand)| {
+                    let cards: Vec<u8> = hand.chars().map(|c| char_to_val(&c)).collect();
+                    (Self::group_sizes(hand, part == 2), cards)
+                });
+                AocRes::Int32(Self::total_winnings(&hands))
+            }),
+        ]
+    }
+
+    // Every hand in input order with its type under both rule sets
+    fn snapshot(&self) -> Option<String> {
+        let part1 = Self::read_hands(&self.path_to_input, Self::categorize_type_part1);
+        let part2 = Self::read_hands(&self.path_to_input, Self::categorize_type_part2);
+        let lines: Vec<String> = part1
+            .iter()
+            .zip(&part2)
+            .map(|((type1, bid, hand), (type2, _, _))| {
+                format!("{} {}: part1 {:?}, part2 {:?}", hand, bid, type1, type2)
+            })
+            .collect();
+        Some(lines.join("\n") + "\n")
+    }
+
+}
+
+impl Day7 {
+
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let hand_and_bid = Pattern::new(HAND);
+        lines
             .iter()
-            .fold((1, 0), |(rank, sum), (handtype, bid, hand)| {
-                (rank + 1, sum + rank * bid)
-            });
-        // println!("total_sum: {}", total_sum);
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let error = Self::parse_hand(&hand_and_bid, line).err()?;
+                Some(error.to_input_error(i, line))
+            })
+            .collect()
+    }
 
-        AocRes::Int32(total_sum)
+    // Reads every hand with its bid and sorts them from the weakest to the strongest
+    fn read_hands(
+        path_to_input: &str,
+        categorize: fn(&str) -> (HandTypes, String),
+    ) -> Vec<(HandTypes, i32, String)> {
+        Self::try_read_hands(path_to_input, categorize).unwrap_or_else(|error| panic!("{}", error))
     }
 
-    fn part2(&self) -> AocRes {
-        let mut total_sum = 0;
+    fn try_read_hands(
+        path_to_input: &str,
+        categorize: fn(&str) -> (HandTypes, String),
+    ) -> Result<Vec<(HandTypes, i32, String)>, String> {
+        let hand_and_bid = Pattern::new(HAND);
+        let lines = input::read_lines(path_to_input).map_err(|e| format!("{}: {}", path_to_input, e))?;
         let mut hands = Vec::<(HandTypes, i32, String)>::new();
+        for (i, l) in lines.iter().enumerate() {
+            let (hand, bid) = Self::parse_hand(&hand_and_bid, l)
+                .map_err(|error| format!("{}:{}: {}", path_to_input, i + 1, error.message))?;
+            let (hand_type, _sorted) = categorize(hand);
+            // hands.push((hand_type, bid, sorted));
+            hands.push((hand_type, bid, hand.to_string()));
+        }
+        Ok(hands)
+    }
 
-        if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-            // Consumes the iterator, returns an (Optional) String
-            for line in lines {
-                if let Ok(l) = line {
-                    let mut v = l.split(" ").collect::<VecDeque<&str>>();
-                    let Some(bid) = v.pop_back() else {
-                        panic!("Could not parse bid!");
-                    };
-                    let bid = bid.to_string().parse::<i32>().unwrap();
-                    let Some(hand) = v.pop_front() else {
-                        panic!("Could not parse hand!");
-                    };
-                    let (hand_type, sorted) = Self::categorize_type_part2(hand);
-                    // hands.push((hand_type, bid, sorted));
-                    hands.push((hand_type, bid, hand.to_string()));
-                }
-            }
+    // One line like '32T3K 765', surrounding spaces are fine
+    fn parse_hand<'t>(hand_and_bid: &Pattern, line: &'t str) -> Result<(&'t str, i32), ScanError> {
+        let fields = hand_and_bid.matches(line.trim_start()).map_err(|_| {
+            let message = "expected a hand and a bid like '32T3K 765'".to_string();
+            ScanError::new(0, line.len(), message)
+        })?;
+        let leading = line.len() - line.trim_start().len();
+        let hand = fields.str("hand");
+        // Sorting looks at exactly five cards
+        let num_cards = hand.chars().count();
+        if num_cards != 5 {
+            let message = format!("hand must have 5 cards, found {}", num_cards);
+            return Err(ScanError::new(leading, hand.len(), message));
         }
+        // char_to_val_part1 quietly maps anything it doesn't know to 0
+        if let Some((at, card)) = hand.char_indices().find(|(_, c)| Self::char_to_val_part1(c) == 0) {
+            let message = format!("unknown card '{}'", card);
+            return Err(ScanError::new(leading + at, card.len_utf8(), message));
+        }
+        let bid = fields.get("bid").map_err(|error| error.shifted(leading))?;
+        Ok((hand, bid))
+    }
 
-        //hands.sort_unstable_by_key(|h| Self::hand_type_to_comparator(h.0));
+    fn sorted_hands(
+        path_to_input: &str,
+        categorize: fn(&str) -> (HandTypes, String),
+        char_to_val: fn(&char) -> u8,
+    ) -> Vec<(HandTypes, i32, String)> {
+        let mut hands = Self::read_hands(path_to_input, categorize);
         hands.sort_unstable_by_key(|h| {
             let v = h.2.chars().collect::<Vec<char>>();
             let (c1, c2, c3, c4, c5) = (v[0], v[1], v[2], v[3], v[4]);
             (
                 Self::hand_type_to_val(h.0),
-                Self::char_to_val_part2(&c1),
-                Self::char_to_val_part2(&c2),
-                Self::char_to_val_part2(&c3),
-                Self::char_to_val_part2(&c4),
-                Self::char_to_val_part2(&c5),
+                char_to_val(&c1),
+                char_to_val(&c2),
+                char_to_val(&c3),
+                char_to_val(&c4),
+                char_to_val(&c5),
             )
         });
         // for h in &hands {
         //     println!("hand: {}, bid: {}", h.2, h.1);
         // }
-        let (total_ranks, total_sum) = hands
-            .iter()
-            .fold((1, 0), |(rank, sum), (handtype, bid, hand)| {
-                (rank + 1, sum + rank * bid)
-            });
-        // println!("total_sum: {}", total_sum);
-
-        AocRes::Int32(total_sum)
+        hands
     }
 
-}
-
-impl Day7 {
+    // Sum of rank times bid, ranks counting up from the weakest hand
+    fn total_winnings(hands: &[(HandTypes, i32, String)]) -> i32 {
+        (1..).zip(hands).map(|(rank, (_, bid, _))| rank * bid).sum()
+    }
 
-    fn char_to_val_part1(c: &char) -> u8 {
+    pub fn char_to_val_part1(c: &char) -> u8 {
         match c {
             '2' => 2,
             '3' => 3,
@@ -156,7 +243,7 @@ impl Day7 {
         }
     }
 
-    fn char_to_val_part2(c: &char) -> u8 {
+    pub fn char_to_val_part2(c: &char) -> u8 {
         match c {
             '2' => 2,
             '3' => 3,
@@ -175,54 +262,38 @@ impl Day7 {
         }
     }
 
-    fn hand_type_to_val(hand_type: HandTypes) -> u8 {
+    pub fn hand_type_to_val(hand_type: HandTypes) -> u8 {
         match hand_type {
-            HandTypes::FiveOfAKind(_) => std::u8::MAX,
-            HandTypes::FourOfAKind(_, _) => std::u8::MAX - 1,
-            HandTypes::FullHouse(_, _) => std::u8::MAX - 2,
-            HandTypes::ThreeOfAKind(_, _, _) => std::u8::MAX - 3,
-            HandTypes::TwoPair(_, _, _) => std::u8::MAX - 4,
-            HandTypes::OnePair(_, _, _, _) => std::u8::MAX - 5,
-            HandTypes::HighCard(_, _, _, _, _) => std::u8::MAX - 6,
+            HandTypes::FiveOfAKind(_) => u8::MAX,
+            HandTypes::FourOfAKind(_, _) => u8::MAX - 1,
+            HandTypes::FullHouse(_, _) => u8::MAX - 2,
+            HandTypes::ThreeOfAKind(_, _, _) => u8::MAX - 3,
+            HandTypes::TwoPair(_, _, _) => u8::MAX - 4,
+            HandTypes::OnePair(_, _, _, _) => u8::MAX - 5,
+            HandTypes::HighCard(_, _, _, _, _) => u8::MAX - 6,
             HandTypes::Unknown => 0,
         }
     }
 
-    fn hand_type_to_comparator(hand_type: HandTypes) -> (u8, u8, u8, u8, u8) {
-        match hand_type {
-            HandTypes::Unknown => (0, 0, 0, 0, 0),
-            HandTypes::HighCard(c1, c2, c3, c4, c5) => (c1, c2, c3, c4, c5),
-            HandTypes::OnePair(c1, c2, c3, c4) => {
-                (Self::hand_type_to_val(hand_type), c1, c2, c3, c4)
+    // The sizes of the groups of equal cards, largest first, so that comparing them
+    // ranks the hands like their types do. Wild jokers join the largest group
+    fn group_sizes(hand: &str, jokers_wild: bool) -> Vec<u8> {
+        let mut counts = HashMap::<char, u8>::new();
+        let mut jokers = 0;
+        for c in hand.chars() {
+            if jokers_wild && c == 'J' {
+                jokers += 1;
+            } else {
+                *counts.entry(c).or_default() += 1;
             }
-            HandTypes::TwoPair(c1, c2, c3) => {
-                (Self::hand_type_to_val(hand_type), std::u8::MAX, c1, c2, c3)
-            }
-            HandTypes::ThreeOfAKind(c1, c2, c3) => {
-                (Self::hand_type_to_val(hand_type), std::u8::MAX, c1, c2, c3)
-            }
-            HandTypes::FullHouse(c1, c2) => (
-                Self::hand_type_to_val(hand_type),
-                std::u8::MAX,
-                std::u8::MAX,
-                c1,
-                c2,
-            ),
-            HandTypes::FourOfAKind(c1, c2) => (
-                Self::hand_type_to_val(hand_type),
-                std::u8::MAX,
-                std::u8::MAX,
-                c1,
-                c2,
-            ),
-            HandTypes::FiveOfAKind(c1) => (
-                Self::hand_type_to_val(hand_type),
-                std::u8::MAX,
-                std::u8::MAX,
-                std::u8::MAX,
-                c1,
-            ),
         }
+        let mut sizes: Vec<u8> = counts.into_values().collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        match sizes.first_mut() {
+            Some(largest) => *largest += jokers,
+            None => sizes.push(jokers),
+        }
+        sizes
     }
 
     fn find_repeats_part1(mut sorted: Vec<char>) -> Vec<(u8, u8)> {
@@ -230,30 +301,27 @@ impl Day7 {
         let mut char_counts = Vec::new();
         let l = sorted.len();
         let mut curr = sorted.pop();
-        let mut next = curr.clone();
-        for i in 0..l {
-            match curr {
-                Some(c1) => {
-                    next = sorted.pop();
-                    match next {
-                        Some(c2) => {
-                            if c1 == c2 {
-                                count = count + 1
-                            } else {
-                                // println!("sorted: {}, count: {} char: {}",
-                                //   sorted.clone().into_iter().collect::<String>(), count, c1);
-                                char_counts.push((count, Self::char_to_val_part1(&c1)));
-                                count = 1;
-                            }
-                        }
-                        None => {
+        let mut next = curr;
+        for _i in 0..l {
+            if let Some(c1) = curr {
+                next = sorted.pop();
+                match next {
+                    Some(c2) => {
+                        if c1 == c2 {
+                            count += 1
+                        } else {
                             // println!("sorted: {}, count: {} char: {}",
                             //   sorted.clone().into_iter().collect::<String>(), count, c1);
                             char_counts.push((count, Self::char_to_val_part1(&c1)));
+                            count = 1;
                         }
                     }
+                    None => {
+                        // println!("sorted: {}, count: {} char: {}",
+                        //   sorted.clone().into_iter().collect::<String>(), count, c1);
+                        char_counts.push((count, Self::char_to_val_part1(&c1)));
+                    }
                 }
-                None => (),
             }
             curr = next
         }
@@ -270,27 +338,15 @@ impl Day7 {
         let mut char_counts = Vec::new();
         let l = sorted.len();
         let mut curr = sorted.pop();
-        let mut next = curr.clone();
-        for i in 0..l {
-            match curr {
-                Some(c1) => {
-                    next = sorted.pop();
-                    match next {
-                        Some(c2) => {
-                            if c1 == c2 {
-                                count = count + 1
-                            } else {
-                                // println!("sorted: {}, count: {} char: {}",
-                                //   sorted.clone().into_iter().collect::<String>(), count, c1);
-                                if c1 == 'J' {
-                                    joker_count = count;
-                                } else {
-                                    char_counts.push((count, Self::char_to_val_part1(&c1)));
-                                }
-                                count = 1;
-                            }
-                        }
-                        None => {
+        let mut next = curr;
+        for _i in 0..l {
+            if let Some(c1) = curr {
+                next = sorted.pop();
+                match next {
+                    Some(c2) => {
+                        if c1 == c2 {
+                            count += 1
+                        } else {
                             // println!("sorted: {}, count: {} char: {}",
                             //   sorted.clone().into_iter().collect::<String>(), count, c1);
                             if c1 == 'J' {
@@ -298,15 +354,24 @@ impl Day7 {
                             } else {
                                 char_counts.push((count, Self::char_to_val_part1(&c1)));
                             }
+                            count = 1;
+                        }
+                    }
+                    None => {
+                        // println!("sorted: {}, count: {} char: {}",
+                        //   sorted.clone().into_iter().collect::<String>(), count, c1);
+                        if c1 == 'J' {
+                            joker_count = count;
+                        } else {
+                            char_counts.push((count, Self::char_to_val_part1(&c1)));
                         }
                     }
                 }
-                None => (),
             }
             curr = next
         }
 
-        if char_counts.len() == 0 && joker_count == 5 {
+        if char_counts.is_empty() && joker_count == 5 {
             // edge case where all jokers - then no values pushed
             char_counts.push((joker_count, Self::char_to_val_part1(&'J')));
         } else {
@@ -392,31 +457,24 @@ impl Day7 {
         hand_type
     }
 
-    fn categorize_type_part1(hand: &str) -> (HandTypes, String) {
+    pub fn categorize_type_part1(hand: &str) -> (HandTypes, String) {
         let mut chars: Vec<char> = hand.chars().collect();
         chars.sort_by(|a, b| Self::char_to_val_part1(a).cmp(&(Self::char_to_val_part1(b))));
         let sorted = chars.clone().into_iter().collect::<String>();
-        let mut char_counts: Vec<(u8, u8)> = Self::find_repeats_part1(chars.clone());
+        let char_counts: Vec<(u8, u8)> = Self::find_repeats_part1(chars.clone());
         let hand_type = Self::parse_char_counts(char_counts);
         // type, typedata, hand, bid
         (hand_type, sorted)
     }
 
-    fn categorize_type_part2(hand: &str) -> (HandTypes, String) {
+    pub fn categorize_type_part2(hand: &str) -> (HandTypes, String) {
         let mut chars: Vec<char> = hand.chars().collect();
         chars.sort_by(|a, b| Self::char_to_val_part1(a).cmp(&(Self::char_to_val_part1(b))));
         let sorted = chars.clone().into_iter().collect::<String>();
-        let mut char_counts: Vec<(u8, u8)> = Self::find_repeats_part2(chars.clone());
+        let char_counts: Vec<(u8, u8)> = Self::find_repeats_part2(chars.clone());
         let hand_type = Self::parse_char_counts(char_counts);
         // type, typedata, hand, bid
         (hand_type, sorted)
     }
 
-    fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-    where
-        P: AsRef<Path>,
-    {
-        let file = File::open(filename)?;
-        Ok(io::BufReader::new(file).lines())
-    }
 }
@@ -1,9 +1,16 @@
 use crate::aoc::{Aoc, AocRes};
+use crate::input;
+use crate::graph;
+use crate::math;
+use crate::check::{column_of, InputError};
+use crate::progress::Progress;
+use crate::scan::{Fields, Pattern, ScanError};
+use crate::variants::Variant;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
 use std::path::Path;
 
+const NODE: &str = "{key} = ({left}, {right})";
+
 // Dynamic dispatch just for learning
 pub trait Neighbour {
     fn get_left(&self) -> String;
@@ -11,16 +18,16 @@ pub trait Neighbour {
 }
 
 pub struct DesertNode {
-    left: String,
-    right: String,
+    pub left: String,
+    pub right: String,
 }
 
 impl Neighbour for DesertNode {
     fn get_left(&self) -> String {
-        return self.left.clone();
+        self.left.clone()
     }
     fn get_right(&self) -> String {
-        return self.right.clone();
+        self.right.clone()
     }
 }
 
@@ -28,6 +35,12 @@ pub struct DesertMap {
     pub nodes: HashMap<String, Box<dyn Neighbour>>,
 }
 
+impl Default for DesertMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DesertMap {
     pub fn new() -> Self {
         Self {
@@ -39,9 +52,10 @@ impl DesertMap {
         dmap.nodes.insert(key, node);
     }
 
+    // Debugging aid, on stderr so it never mixes with the answers
     pub fn list_nodes(dmap: &Self) {
         for (k, v) in &dmap.nodes {
-            println!(
+            eprintln!(
                 "self: {}, left: {}, right: {}",
                 k,
                 v.get_left(),
@@ -50,48 +64,42 @@ impl DesertMap {
         }
     }
 
-    fn find_path(
+    // Follows the directions from start_key one (node, direction) state at a
+    // time until end_cond_fn holds, which may be right away
+    pub fn find_path(
         dmap: &Self,
-        start_key: &String,
-        directions: &String,
-        end_cond_fn: &dyn Fn(&String) -> bool,
+        start_key: &str,
+        directions: &str,
+        end_cond_fn: &dyn Fn(&str) -> bool,
     ) -> i32 {
         let dir_vec: Vec<char> = directions.chars().collect();
-        let mut key = start_key.clone();
-        let mut steps = 0;
-        while !(end_cond_fn(&key)) {
-            for dir in &dir_vec {
-                key = Self::find_next_key(dmap, dir, &key);
-                steps = steps + 1;
-                if end_cond_fn(&key) {
-                    // dbg!((&key, &dir, &steps));
-                    break;
-                }
-            }
-        }
-        steps
-    }
-
-    // taken from https://gist.github.com/victor-iyi/8a84185c1d52419b0d4915a648d5e3e1
-    pub fn gcd(mut n: u64, mut m: u64) -> u64 {
-        assert!(n != 0 && m != 0);
-        while m != 0 {
-            if m < n {
-                std::mem::swap(&mut m, &mut n);
+        let progress = Progress::current();
+        let step = |(key, i): &(String, usize)| {
+            (Self::find_next_key(dmap, &dir_vec[*i], key), (i + 1) % dir_vec.len())
+        };
+        // Only the walk counts as progress, not the search for its cycle
+        let done = |(key, _): &(String, usize)| {
+            let done = end_cond_fn(key);
+            if !done {
+                progress.add(1);
             }
-            m %= n;
-        }
-        n
+            done
+        };
+        let start = (start_key.to_string(), 0);
+        let Some(steps) = graph::steps_until(start, step, done) else {
+            panic!("Walking from {} never reaches an end node!", start_key);
+        };
+        steps as i32
     }
 
     // Brute forcing this is NOT the way to go
-    // Try using Lowest Common Denominator to solve this problem instead
+    // Try using Lowest Common Multiple to solve this problem instead
     // Algo:
     // - Find num steps for each one
-    // - Find lowest common denominator for all steps
-    pub fn find_path_2(dmap: &Self, directions: &String) -> u64 {
+    // - Find lowest common multiple for all steps
+    pub fn find_path_2(dmap: &Self, directions: &str) -> u64 {
         let start_keys = Self::get_starting_keys(dmap);
-        fn ends_with_z(s: &String) -> bool {
+        fn ends_with_z(s: &str) -> bool {
             let last = s.chars().last().unwrap();
             last == 'Z'
         }
@@ -102,25 +110,34 @@ impl DesertMap {
             steps_vector.push(steps as u64);
         }
 
-        let Some(mut a) = steps_vector.pop() else {
+        if steps_vector.is_empty() {
             panic!("empty steps");
+        }
+        let Some(lcm) = math::lcm_all(&steps_vector) else {
+            panic!("the lowest common multiple of {:?} does not fit in a u64", steps_vector);
         };
-        let mut gcd = a;
-        let mut lcm: u128 = a as u128;
-        for _i in 0..steps_vector.len() {
-            let Some(b) = steps_vector.pop() else {
-                panic!("empty steps");
-            };
-            gcd = Self::gcd(a, b);
-            lcm = (lcm as u128) * (b as u128) / gcd as u128;
-            a = gcd;
-            // println!("gcd: {}", gcd);
-            // println!("lcm: {}", lcm);
+        lcm
+    }
+
+    // Walks every ghost at once until they all stand on a ..Z node, the way
+    // find_path_2 would have to without the LCM shortcut
+    pub fn find_path_lockstep(dmap: &Self, directions: &str) -> u64 {
+        let dir_vec: Vec<char> = directions.chars().collect();
+        let progress = Progress::current();
+        let mut keys = Self::get_starting_keys(dmap);
+        let mut steps: u64 = 0;
+        while !keys.iter().all(|k| k.ends_with('Z')) {
+            let dir = &dir_vec[steps as usize % dir_vec.len()];
+            for key in &mut keys {
+                *key = Self::find_next_key(dmap, dir, key);
+            }
+            steps += 1;
+            progress.add(1);
         }
-        lcm as u64
+        steps
     }
 
-    pub fn find_next_key(dmap: &Self, direction: &char, key: &String) -> String {
+    pub fn find_next_key(dmap: &Self, direction: &char, key: &str) -> String {
         let Some(node) = dmap.nodes.get(key) else {
             panic!("Node not found in map!");
         };
@@ -134,7 +151,7 @@ impl DesertMap {
 
     fn get_starting_keys(dmap: &Self) -> Vec<String> {
         let mut starts = Vec::<String>::new();
-        for (k, v) in &dmap.nodes {
+        for k in dmap.nodes.keys() {
             let last = k.chars().last().unwrap();
             if last == 'A' {
                 starts.push(k.clone());
@@ -152,19 +169,19 @@ pub struct Day8 {
 
 impl Aoc for Day8 {
 
-    fn new(path_to_input: &String) -> Self {
+    fn new(path_to_input: &str) -> Self {
         Self {
-            path_to_input: path_to_input.clone()
+            path_to_input: path_to_input.to_string()
         }
     }
 
     fn part1(&self) -> AocRes {
         let (dmap, directions) = Self::create_dmap(&self.path_to_input);
         // DesertMap::list_nodes(&dmap);
-        fn zzz(s: &String) -> bool {
-            s.clone() == "ZZZ".to_string()
+        fn zzz(s: &str) -> bool {
+            s == "ZZZ"
         }
-        let ans = DesertMap::find_path(&dmap, &"AAA".to_string(), &directions, &zzz);
+        let ans = DesertMap::find_path(&dmap, "AAA", &directions, &zzz);
 
         AocRes::Int32(ans)
     }
@@ -176,51 +193,174 @@ impl Aoc for Day8 {
         AocRes::UInt64(ans)
     }
 
+    // 2: part 2 folds the cycle lengths with the lcm instead of multiplying them
+    fn version(&self) -> &str {
+        "2"
+    }
+
+    fn parse(&self) -> Result<(), String> {
+        Self::try_create_dmap(&self.path_to_input).map(drop)
+    }
+
+    fn variants(&self, part: u32) -> Vec<Variant<'_>> {
+        if part != 2 {
+            return Vec::new();
+        }
+        vec![
+            Variant::new("lcm", || self.part2()),
+            Variant::new("lockstep", || {
+                let (dmap, directions) = Self::create_dmap(&self.path_to_input);
+                AocRes::UInt64(DesertMap::find_path_lockstep(&dmap, &directions))
+            })
+            .slow(),
+        ]
+    }
+
 }
 
 impl Day8 {
 
+    pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+        let node_pattern = Pattern::new(NODE);
+        let mut errors = Vec::new();
+        let directions = lines.first().unwrap_or(&"");
+        if let Err(error) = Self::parse_directions(directions) {
+            errors.push(error.to_input_error(0, directions));
+        }
+        if let Some(line) = lines.get(1).filter(|l| !l.is_empty()) {
+            errors.push(InputError::whole_line(
+                1,
+                line,
+                "expected a blank line after the directions".to_string(),
+            ));
+        }
+        let mut defined = HashMap::<&str, (usize, &str, &str)>::new();
+        let mut references = Vec::new();
+        for (i, line) in lines.iter().enumerate().skip(2) {
+            let fields = match Self::parse_node(&node_pattern, line) {
+                Ok(fields) => fields,
+                Err(error) => {
+                    errors.push(error.to_input_error(i, line));
+                    continue;
+                }
+            };
+            let (left, right) = (fields.str("left"), fields.str("right"));
+            defined.insert(fields.str("key"), (i, left, right));
+            references.push((i, left, fields.offset("left")));
+            references.push((i, right, fields.offset("right")));
+        }
+        for (i, node, offset) in references {
+            if !defined.contains_key(node) {
+                errors.push(InputError::new(
+                    i,
+                    column_of(lines[i], offset),
+                    3,
+                    format!("node '{}' is never defined", node),
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            return errors;
+        }
+        if !defined.contains_key("AAA") {
+            errors.push(InputError::new(2, 0, 0, "there is no node 'AAA' to start from".to_string()));
+        }
+        let mut starts: Vec<&str> = defined.keys().copied().filter(|k| k.ends_with('A')).collect();
+        starts.sort();
+        for start in starts {
+            // Part 1 walks from AAA to ZZZ, part 2 from every ..A to any ..Z
+            let end = |k: &str| if start == "AAA" { k == "ZZZ" } else { k.ends_with('Z') };
+            if !Self::walk_ends(&defined, start, directions, end) {
+                let line = defined[start].0;
+                errors.push(InputError::new(
+                    line,
+                    0,
+                    3,
+                    format!("walking from '{}' never reaches an end node", start),
+                ));
+            }
+        }
+        errors
+    }
+
+    // The line of L and R directions, which has to have at least one of them
+    fn parse_directions(line: &str) -> Result<&str, ScanError> {
+        if line.is_empty() {
+            let message = "expected a line of L and R directions".to_string();
+            return Err(ScanError::new(0, 0, message));
+        }
+        match line.char_indices().find(|&(_, c)| c != 'L' && c != 'R') {
+            Some((at, c)) => Err(ScanError::new(at, c.len_utf8(), format!("unknown direction '{}'", c))),
+            None => Ok(line),
+        }
+    }
+
+    // One line like 'AAA = (BBB, CCC)', every name three letters or digits
+    fn parse_node<'p, 't>(node: &'p Pattern, line: &'t str) -> Result<Fields<'p, 't>, ScanError> {
+        let fields = node.matches(line).ok().filter(|fields| {
+            ["key", "left", "right"].iter().all(|name| Self::is_node_name(fields.str(name)))
+        });
+        fields.ok_or_else(|| {
+            let message = "expected a node like 'AAA = (BBB, CCC)'".to_string();
+            ScanError::new(0, line.len(), message)
+        })
+    }
+
+    fn is_node_name(name: &str) -> bool {
+        name.len() == 3 && name.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+    }
+
+    // Follows the directions from `start` and tells whether `end` ever holds
+    fn walk_ends(
+        nodes: &HashMap<&str, (usize, &str, &str)>,
+        start: &str,
+        directions: &str,
+        end: impl Fn(&str) -> bool,
+    ) -> bool {
+        let directions: Vec<char> = directions.chars().collect();
+        let step = |&(key, step): &(&str, usize)| {
+            let (_, left, right) = nodes[key];
+            let key = if directions[step] == 'L' { left } else { right };
+            (key, (step + 1) % directions.len())
+        };
+        // The first step is always taken, even from a node that is already an end
+        graph::steps_until(step(&(start, 0)), step, |&(key, _)| end(key)).is_some()
+    }
+
     fn create_dmap<P>(path_to_input: P) -> (DesertMap, String)
     where
         P: AsRef<Path>,
     {
-        let file = File::open(path_to_input).unwrap();
-        let mut buffer = io::BufReader::new(file);
+        Self::try_create_dmap(path_to_input).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_create_dmap<P>(path_to_input: P) -> Result<(DesertMap, String), String>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path_to_input.as_ref();
+        let text = input::read_input(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let lines = input::lines(&text);
+        let node_pattern = Pattern::new(NODE);
 
         let mut dmap = DesertMap::new();
-        let Some(mut directions) = Self::read_line(&mut buffer) else {
-            panic!("Failed to read directions");
+        let Some(directions) = lines.first() else {
+            return Err("Failed to read directions".to_string());
         };
-        // Remove last "\n" character
-        directions = directions[..(directions.len() - 1)].to_string();
-        while let Some(contents) = Self::read_line(&mut buffer) {
-            if contents != "\n" {
-                let s = contents.split(" = ").collect::<Vec<&str>>();
-                let key = s[0].to_string();
-                let n = s[1].to_string();
-                let neighbours = n.split(", ").collect::<Vec<&str>>();
-                let n1 = neighbours[0][1..].to_string();
-                let n2 = neighbours[1][..3].to_string();
-                let node = Box::new(DesertNode {
-                    left: n1,
-                    right: n2,
-                });
-                DesertMap::insert_node(&mut dmap, key, node);
-            }
-        }
-        (dmap, directions)
-    }
-
-    fn read_line(buffer: &mut io::BufReader<File>) -> Option<String> {
-        let mut line = String::new();
-        if let Ok(num_bytes) = buffer.read_line(&mut line) {
-            match num_bytes {
-                0 => None,
-                _ => Some(line),
-            }
-        } else {
-            panic!("Error occured while reading file!");
+        let directions = Self::parse_directions(directions)
+            .map_err(|error| format!("{}:1: {}", path.display(), error.message))?;
+        for (i, contents) in lines.iter().enumerate().skip(1).filter(|(_, l)| !l.is_empty()) {
+            let fields = match Self::parse_node(&node_pattern, contents) {
+                Ok(fields) => fields,
+                Err(error) => return Err(format!("{}:{}: {}", path.display(), i + 1, error.message)),
+            };
+            let node = Box::new(DesertNode {
+                left: fields.str("left").to_string(),
+                right: fields.str("right").to_string(),
+            });
+            DesertMap::insert_node(&mut dmap, fields.str("key").to_string(), node);
         }
+        Ok((dmap, directions.to_string()))
     }
 }
 
@@ -230,7 +370,7 @@ mod tests {
 
     #[test]
     fn part1_works_on_sample_input() {
-        let day8 = Day8::new(&"./inputs/day8_test.input".to_string());
+        let day8 = Day8::new("./inputs/day8_test.input");
         let AocRes::Int32(res) = day8.part1() else {
             panic!("Failed to get result from part 1!")
         };
@@ -239,7 +379,7 @@ mod tests {
 
     #[test]
     fn part1_works_on_sample_input_2() {
-        let day8 = Day8::new(&"./inputs/day8_test2.input".to_string());
+        let day8 = Day8::new("./inputs/day8_test2.input");
         let AocRes::Int32(res) = day8.part1() else {
             panic!("Failed to get result from part 1!")
         };
@@ -249,10 +389,24 @@ mod tests {
     #[test]
     fn part2_works_on_sample_input() {
 
-        let day8 = Day8::new(&"./inputs/day8_p2_test.input".to_string());
+        let day8 = Day8::new("./inputs/day8_p2_test.input");
         let AocRes::UInt64(res) = day8.part2() else {
             panic!("Failed to get result from part 1!")
         };
         assert!(res == 6);
     }
+
+    #[test]
+    fn parts_stop_at_an_empty_directions_line() {
+        // With no directions to follow the walks used to go on forever
+        let path = std::env::temp_dir().join(format!("aoc-day8-{}.input", std::process::id()));
+        std::fs::write(&path, "\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        let day8 = Day8::new(path.to_str().unwrap());
+        assert!(day8.parse().unwrap_err().ends_with("expected a line of L and R directions"));
+        for part in 1..=2 {
+            let run = std::panic::catch_unwind(|| crate::runner::solve_part(&day8, part));
+            assert!(run.is_err(), "part {}", part);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
 }
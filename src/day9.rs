@@ -1,8 +1,11 @@
 use crate::aoc::{Aoc, AocRes};
+use crate::input;
+use crate::check::InputError;
+use crate::numeric::Num;
+use crate::scan::{Pattern, ScanError};
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+
+const HISTORY: &str = "{history}";
 
 pub struct Day9 {
   path_to_input: String
@@ -10,36 +13,21 @@ pub struct Day9 {
 
 impl Aoc for Day9 {
 
-  fn new(path_to_input: &String) -> Self {
+  fn new(path_to_input: &str) -> Self {
       Self {
-          path_to_input: path_to_input.clone()
+          path_to_input: path_to_input.to_string()
       }
   }
 
   fn part1(&self) -> AocRes {
     
-    let mut ans: i32 = 0;
+    let mut ans = Num(0i32);
     
-    if let Ok(lines) = Self::read_lines(&self.path_to_input) {
-      // Consumes the iterator, returns an (Optional) String
-      for line in lines {
-          if let Ok(l) = line {
-            let v = l.split(" ").collect::<Vec<&str>>();
-            let mut nums = VecDeque::<i32>::new();
-            for el in v {
-              nums.push_back(el.parse::<i32>().unwrap());
-            }
-            // recursion problem
-            //dbg!(nums.clone());
-            let extrapolated = Self::differentiate(nums);
-            println!("{}", extrapolated);
-            // dbg!((ans, extrapolated));
-            ans = ans + extrapolated;
-          }
-      }
+    for nums in Self::read_histories(&self.path_to_input) {
+      ans += Self::extrapolate(&Self::difference_table(nums));
     }
 
-    AocRes::Int32(ans)
+    AocRes::Int32(ans.get())
   }
 
 
@@ -47,32 +35,84 @@ impl Aoc for Day9 {
     AocRes::Int32(0)
   }
 
+  fn parse(&self) -> Result<(), String> {
+    Self::try_read_histories(&self.path_to_input).map(drop)
+  }
+
+  // The table of differences of every history, each row under the gaps of the one above
+  fn snapshot(&self) -> Option<String> {
+    let histories = Self::try_read_histories(&self.path_to_input).ok()?;
+    let mut out = String::new();
+    for history in histories {
+      let table = Self::difference_table(history);
+      // An even width, so every row can start half a cell further in
+      let longest = table.iter().flatten().map(|n| n.to_string().len()).max().unwrap_or(1);
+      let width = (longest + 2) / 2 * 2;
+      for (depth, row) in table.iter().enumerate() {
+        let cells: Vec<String> = row.iter().map(|n| format!("{:>w$}", n, w = width)).collect();
+        let line = " ".repeat(depth * width / 2) + &cells.concat();
+        out.push_str(line.trim_end());
+        out.push('\n');
+      }
+      out.push('\n');
+    }
+    Some(out)
+  }
+
 }
 
 impl Day9 {
 
-  fn differentiate(v: VecDeque::<i32>) -> i32 {
-    let mut new_v = VecDeque::<i32>::new();
-    for i in 0..v.len()-1 {
-      new_v.push_back(v[i + 1] - v[i]);
+  pub fn check_input(lines: &[&str]) -> Vec<InputError> {
+    let history = Pattern::new(HISTORY);
+    lines.iter().enumerate()
+      .filter_map(|(i, line)| {
+        let error = Self::parse_history(&history, line).err()?;
+        Some(error.to_input_error(i, line))
+      })
+      .collect()
+  }
+
+  fn read_histories(path_to_input: &str) -> Vec<VecDeque<Num<i32>>> {
+    Self::try_read_histories(path_to_input).unwrap_or_else(|error| panic!("{}", error))
+  }
+
+  // One history of numbers per line, an unreadable input has none
+  fn try_read_histories(path_to_input: &str) -> Result<Vec<VecDeque<Num<i32>>>, String> {
+    let history = Pattern::new(HISTORY);
+    let lines = input::read_lines(path_to_input).map_err(|e| format!("{}: {}", path_to_input, e))?;
+    lines.iter().enumerate()
+      .map(|(i, l)| {
+        Self::parse_history(&history, l)
+          .map_err(|error| format!("{}:{}: {}", path_to_input, i + 1, error.message))
+      })
+      .collect()
+  }
+
+  // One line of numbers that each fit in an i32
+  fn parse_history(history: &Pattern, line: &str) -> Result<VecDeque<Num<i32>>, ScanError> {
+    let numbers: Vec<i32> = history.matches(line)?.list("history")?;
+    if numbers.is_empty() {
+      return Err(ScanError::new(0, line.len(), "expected a history of numbers".to_string()));
     }
-    if (&new_v).iter().all(|&x| x == 0) {
-      dbg!((v.clone(), &new_v.clone(), 0));
-      return v[v.len()-1];
-    } else {
-      let next_num = v[v.len()-1] + Self::differentiate(new_v.clone());
-      dbg!((&v.clone(), next_num));
-      return next_num;
+    Ok(numbers.into_iter().map(Num).collect())
+  }
+
+  // The history and its differences down to the first row of zeros
+  fn difference_table(history: VecDeque<Num<i32>>) -> Vec<VecDeque<Num<i32>>> {
+    let mut table = vec![history];
+    while let Some(row) = table.last().filter(|row| row.iter().any(|&n| n != Num(0))) {
+      let next: VecDeque<Num<i32>> = (1..row.len()).map(|i| row[i] - row[i - 1]).collect();
+      table.push(next);
     }
+    table
   }
 
-  fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-  where
-      P: AsRef<Path>,
-  {
-      let file = File::open(filename)?;
-      Ok(io::BufReader::new(file).lines())
+  // The next value of the history, adding up the rows' last values from the bottom row up
+  fn extrapolate(table: &[VecDeque<Num<i32>>]) -> Num<i32> {
+    table.iter().rev().fold(Num(0), |below, row| row.back().map_or(below, |&last| last + below))
   }
+
 }
 
 
@@ -82,7 +122,7 @@ mod tests {
 
     #[test]
     fn part1_works_on_sample_input() {
-        let day9 = Day9::new(&"./inputs/day9_test.input".to_string());
+        let day9 = Day9::new("./inputs/day9_test.input");
         let AocRes::Int32(res) = day9.part1() else {
             panic!("Failed to get result from part 1!")
         };
@@ -91,7 +131,7 @@ mod tests {
 
     #[test]
     fn part1_works_on_sample_input_2() {
-        let day9 = Day9::new(&"./inputs/day9_test2.input".to_string());
+        let day9 = Day9::new("./inputs/day9_test2.input");
         let AocRes::Int32(res) = day9.part1() else {
             panic!("Failed to get result from part 1!")
         };
@@ -0,0 +1,302 @@
+//! Differential testing of the optimized parts against simple reference solutions.
+//!
+//! Some parts lean on shortcuts: the `BinaryHeap` trick in `Day1::part2`, the
+//! LCM shortcut in `DesertMap::find_path_2` and the breadth-first search over
+//! the pipes in `Day10::part1`. Each of those is paired here with a slow but obvious
+//! reference and both are run on generated inputs, smallest first, so the first
+//! disagreement found is also one of the smallest.
+
+use crate::aoc::AocRes;
+use crate::cache::encode;
+use crate::fuzz::panic_message;
+use crate::generate::{generate_input, DIGIT_WORDS};
+use crate::runner::solve_part;
+use crate::solver_for_day;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Tells apart the scratch directories of calls running at the same time
+static SCRATCH_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+pub const DEFAULT_REGRESSION_DIR: &str = "./inputs/regressions";
+
+pub struct Reference {
+    pub day: u32,
+    pub part: u32,
+    pub name: &'static str,
+    /// Returns `None` when the input is too big for the reference to finish
+    pub solve: fn(&str) -> Option<AocRes>,
+}
+
+#[derive(Debug)]
+pub struct Disagreement {
+    pub day: u32,
+    pub part: u32,
+    pub size: usize,
+    pub seed: u64,
+    pub input: String,
+    pub expected: AocRes,
+    /// What the optimized part answered, or why it panicked
+    pub actual: Result<AocRes, String>,
+}
+
+pub fn references() -> Vec<Reference> {
+    vec![
+        Reference {
+            day: 1,
+            part: 2,
+            name: "scan every position for a digit or a digit word",
+            solve: day1_part2,
+        },
+        Reference {
+            day: 8,
+            part: 2,
+            name: "walk every ghost in lockstep",
+            solve: day8_part2,
+        },
+        Reference {
+            day: 10,
+            part: 1,
+            name: "walk the loop once on one thread",
+            solve: day10_part1,
+        },
+    ]
+}
+
+/// Runs the optimized part and `reference` on generated inputs of every size up to
+/// `max_size` and `num_seeds` seeds each. Returns the shortest disagreeing input of the
+/// smallest size that has one. Inputs are written to a scratch directory of
+/// their own for the optimized part to read, failing to do so is an error.
+pub fn find_disagreement(
+    reference: &Reference,
+    max_size: usize,
+    num_seeds: u64,
+) -> io::Result<Option<Disagreement>> {
+    let dir = std::env::temp_dir().join(format!(
+        "aoc-differential-{}-{}",
+        std::process::id(),
+        SCRATCH_DIRS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+    let found = search(reference, max_size, num_seeds, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    found
+}
+
+fn search(
+    reference: &Reference,
+    max_size: usize,
+    num_seeds: u64,
+    dir: &Path,
+) -> io::Result<Option<Disagreement>> {
+    let path = dir.join(format!("day{}.input", reference.day));
+    let mut found: Option<Disagreement> = None;
+    for size in 1..=max_size {
+        for seed in 0..num_seeds {
+            // Days without a generator have nothing to compare
+            let Some(input) = generate_input(reference.day, size, seed) else {
+                return Ok(None);
+            };
+            let Some(expected) = (reference.solve)(&input) else {
+                continue;
+            };
+            fs::write(&path, &input)?;
+            let actual = run_optimized(reference.day, reference.part, &path);
+            if actual.as_ref() == Ok(&expected) {
+                continue;
+            }
+            let shorter = found.as_ref().is_none_or(|f| input.len() < f.input.len());
+            if shorter {
+                found = Some(Disagreement {
+                    day: reference.day,
+                    part: reference.part,
+                    size,
+                    seed,
+                    input,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        if found.is_some() {
+            break;
+        }
+    }
+    Ok(found)
+}
+
+fn run_optimized(day: u32, part: u32, path: &Path) -> Result<AocRes, String> {
+    let path = path.to_string_lossy().to_string();
+    panic::catch_unwind(|| {
+        let solver = solver_for_day(day, &path).unwrap();
+        solve_part(solver.as_ref(), part)
+    })
+    .map_err(|payload| format!("panicked: {}", panic_message(payload.as_ref())))
+}
+
+/// Saves the input of `disagreement` to `dir`, with the reference answer next to it
+/// in a `.answer` file, and returns the path of the input.
+pub fn save_regression<P>(dir: P, disagreement: &Disagreement) -> io::Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let name = format!(
+        "day{}_part{}_size{}_seed{}",
+        disagreement.day, disagreement.part, disagreement.size, disagreement.seed
+    );
+    let input_path = dir.join(format!("{}.input", name));
+    fs::write(&input_path, &disagreement.input)?;
+    fs::write(
+        dir.join(format!("{}.answer", name)),
+        encode(disagreement.expected) + "\n",
+    )?;
+    Ok(input_path)
+}
+
+fn day1_part2(input: &str) -> Option<AocRes> {
+    let digit_at = |line: &str, i: usize| -> Option<i64> {
+        let c = line.as_bytes()[i];
+        if c.is_ascii_digit() {
+            return Some((c - b'0') as i64);
+        }
+        DIGIT_WORDS
+            .iter()
+            .position(|word| line[i..].starts_with(word))
+            .map(|n| n as i64 + 1)
+    };
+    let mut answer = 0;
+    for line in input.lines() {
+        let digits: Vec<i64> = (0..line.len()).filter_map(|i| digit_at(line, i)).collect();
+        answer += digits.first()? * 10 + digits.last()?;
+    }
+    Some(AocRes::Int64(answer))
+}
+
+fn day8_part2(input: &str) -> Option<AocRes> {
+    let mut lines = input.lines();
+    let directions: Vec<char> = lines.next()?.chars().collect();
+    let mut nodes = HashMap::<&str, (&str, &str)>::new();
+    for line in lines.filter(|l| !l.is_empty()) {
+        let (node, next) = line.split_once(" = ")?;
+        let (left, right) = next
+            .trim_matches(|c| c == '(' || c == ')')
+            .split_once(", ")?;
+        nodes.insert(node, (left, right));
+    }
+    let mut ghosts: Vec<&str> = nodes.keys().filter(|n| n.ends_with('A')).copied().collect();
+    let mut steps: u64 = 0;
+    while !ghosts.iter().all(|g| g.ends_with('Z')) {
+        if steps > 10_000_000 {
+            return None;
+        }
+        let direction = directions[steps as usize % directions.len()];
+        for ghost in &mut ghosts {
+            let (left, right) = nodes.get(ghost)?;
+            *ghost = if direction == 'L' { left } else { right };
+        }
+        steps += 1;
+    }
+    Some(AocRes::UInt64(steps))
+}
+
+fn day10_part1(input: &str) -> Option<AocRes> {
+    let grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+    let tile = |x: i64, y: i64| -> char {
+        if x < 0 || y < 0 {
+            return '.';
+        }
+        let row = grid.get(y as usize);
+        *row.and_then(|r| r.get(x as usize)).unwrap_or(&'.')
+    };
+    // Which way each pipe leads, as (dx, dy) pairs
+    let exits = |c: char| -> &'static [(i64, i64)] {
+        match c {
+            '|' => &[(0, -1), (0, 1)],
+            '-' => &[(-1, 0), (1, 0)],
+            'L' => &[(0, -1), (1, 0)],
+            'J' => &[(0, -1), (-1, 0)],
+            '7' => &[(0, 1), (-1, 0)],
+            'F' => &[(0, 1), (1, 0)],
+            _ => &[],
+        }
+    };
+    let (sy, row) = grid
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.contains(&'S'))?;
+    let start = (row.iter().position(|&c| c == 'S')? as i64, sy as i64);
+    // Leave S through any neighbour that leads back into it
+    let (dx, dy) = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+        .into_iter()
+        .find(|&(dx, dy)| {
+            let next = tile(start.0 + dx, start.1 + dy);
+            exits(next).contains(&(-dx, -dy))
+        })?;
+    let (mut prev, mut pos) = (start, (start.0 + dx, start.1 + dy));
+    let mut length = 1;
+    while pos != start {
+        let &(dx, dy) = exits(tile(pos.0, pos.1))
+            .iter()
+            .find(|&&(dx, dy)| (pos.0 + dx, pos.1 + dy) != prev)?;
+        (prev, pos) = (pos, (pos.0 + dx, pos.1 + dy));
+        length += 1;
+    }
+    Some(AocRes::Int32(length / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_solve_the_sample_inputs() {
+        let refs = references();
+        let sample = |path: &str| fs::read_to_string(path).unwrap();
+        assert_eq!(
+            (refs[1].solve)(&sample("./inputs/day8_p2_test.input")),
+            Some(AocRes::UInt64(6))
+        );
+        assert_eq!(
+            (refs[2].solve)(&sample("./inputs/day10_test.input")),
+            Some(AocRes::Int32(8))
+        );
+        assert_eq!(
+            (refs[0].solve)("two1nine\neightwothree\n7pqrstsixteen"),
+            Some(AocRes::Int64(29 + 83 + 76))
+        );
+    }
+
+    #[test]
+    fn optimized_parts_agree_with_their_references() {
+        for reference in &references() {
+            let found = find_disagreement(reference, 12, 5).unwrap();
+            assert!(found.is_none(), "{:?}", found);
+        }
+    }
+
+    #[test]
+    fn reports_the_smallest_disagreement() {
+        let off_by_one = Reference {
+            day: 9,
+            part: 1,
+            name: "always wrong",
+            solve: |_| Some(AocRes::Int32(i32::MIN)),
+        };
+        let found = find_disagreement(&off_by_one, 5, 3).unwrap().unwrap();
+        assert_eq!(found.size, 1);
+        assert!(found.input.lines().count() == 1);
+
+        let dir = std::env::temp_dir().join(format!("aoc-regressions-{}", std::process::id()));
+        let path = save_regression(&dir, &found).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), found.input);
+        let answer = fs::read_to_string(path.with_extension("answer")).unwrap();
+        assert_eq!(answer.trim(), encode(AocRes::Int32(i32::MIN)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,197 @@
+//! Per-record breakdowns of how an answer was reached.
+//!
+//! A day that supports it returns an `Explanation` from `Aoc::explain`: one row
+//! per input record (a line, a game, a card, a hand) with the values that went
+//! into the answer. It renders either as an aligned text table or as JSON.
+
+use crate::aoc::AocRes;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Int(i64),
+    Bool(bool),
+    Text(String),
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Int(value)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Int(value as i64)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(value: usize) -> Self {
+        Cell::Int(value as i64)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(value: bool) -> Self {
+        Cell::Bool(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl Cell {
+    fn to_text(&self) -> String {
+        match self {
+            Cell::Int(n) => n.to_string(),
+            Cell::Bool(true) => "yes".to_string(),
+            Cell::Bool(false) => "no".to_string(),
+            Cell::Text(s) => s.clone(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        match self {
+            Cell::Int(n) => n.to_string(),
+            Cell::Bool(b) => b.to_string(),
+            Cell::Text(s) => json_string(s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+    pub answer: AocRes,
+}
+
+impl Explanation {
+    pub fn new(columns: &[&'static str], answer: AocRes) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+            answer,
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Right-aligns numbers, left-aligns everything else.
+    pub fn to_table(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Cell::to_text).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let widest = cells.iter().map(|row| row[i].chars().count()).max();
+                widest.unwrap_or(0).max(column.len())
+            })
+            .collect();
+        let mut out = String::new();
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, &w)| format!("{:<w$}", column))
+            .collect();
+        writeln!(out, "{}", header.join("  ").trim_end()).unwrap();
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        writeln!(out, "{}", rule.join("  ")).unwrap();
+        for (row, texts) in self.rows.iter().zip(&cells) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(texts)
+                .zip(&widths)
+                .map(|((cell, text), &w)| match cell {
+                    Cell::Int(_) => format!("{:>w$}", text),
+                    _ => format!("{:<w$}", text),
+                })
+                .collect();
+            writeln!(out, "{}", line.join("  ").trim_end()).unwrap();
+        }
+        write!(out, "answer: {}", self.answer).unwrap();
+        out
+    }
+
+    /// One object with the answer and a list of rows keyed by column name.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let fields: Vec<String> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| format!("{}: {}", json_string(column), cell.to_json()))
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            })
+            .collect();
+        format!("{{\"answer\": {}, \"rows\": [{}]}}", self.answer, rows.join(", "))
+    }
+}
+
+/// `s` as a JSON string literal, quotes included.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Explanation {
+        let mut explanation = Explanation::new(&["line", "first", "ok"], AocRes::Int32(7));
+        explanation.push(vec!["a1b\"".into(), 1.into(), true.into()]);
+        explanation.push(vec!["x".into(), 12.into(), false.into()]);
+        explanation
+    }
+
+    #[test]
+    fn renders_an_aligned_table() {
+        assert_eq!(
+            sample().to_table(),
+            "line  first  ok\n----  -----  ---\na1b\"      1  yes\nx        12  no\nanswer: 7"
+        );
+    }
+
+    #[test]
+    fn renders_json() {
+        assert_eq!(
+            sample().to_json(),
+            "{\"answer\": 7, \"rows\": [{\"line\": \"a1b\\\"\", \"first\": 1, \"ok\": true}, \
+             {\"line\": \"x\", \"first\": 12, \"ok\": false}]}"
+        );
+    }
+}
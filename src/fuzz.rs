@@ -0,0 +1,287 @@
+//! Fuzzing the parsers of every day.
+//!
+//! `fuzz_day` is the target: it runs the strict checker and the parse step of
+//! a day, `Aoc::parse`, over arbitrary bytes. Neither may panic, malformed
+//! input has to come back as an error. When the checker accepts the bytes the
+//! parse step must too, and both parts then must not panic either; an overflow
+//! reported as an error is fine. Checked arithmetic is left to the caller,
+//! `aoc fuzz` and the cargo-fuzz targets turn it on. The `fuzz/`
+//! directory wires it up to cargo-fuzz; `fuzz` here is a small mutation-based
+//! loop on stable Rust that seeds its corpus from the inputs directory. Inputs that
+//! panic are shrunk and saved to `tests/fuzz_regressions/`, where
+//! `tests/fuzz.rs` replays them; hangs are only reported.
+
+use crate::check::check_lines;
+use crate::config;
+use crate::input;
+use crate::generate::Rng;
+use crate::runner::try_solve_part;
+use crate::solver_for_day;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_CRASH_DIR: &str = "./tests/fuzz_regressions";
+
+/// How long one input may run before it counts as a hang.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    Panic(String),
+    Hang,
+}
+
+#[derive(Debug)]
+pub struct Crash {
+    pub day: u32,
+    pub input: Vec<u8>,
+    pub failure: Failure,
+}
+
+/// The fuzz target for `day`. Panics when the input crashes the checker or the
+/// parse step or, if the checker accepts it, either part.
+pub fn fuzz_day(day: u32, data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let lines = input::lines(&text);
+    let Some(errors) = check_lines(day, &lines) else {
+        return;
+    };
+    let path = std::env::temp_dir().join(format!(
+        "aoc-fuzz-{}-{:?}.input",
+        std::process::id(),
+        thread::current().id()
+    ));
+    // What the checker saw, the parsers would stop at invalid UTF-8 otherwise
+    fs::write(&path, text.as_bytes()).unwrap();
+    let path_to_input = path.to_string_lossy().to_string();
+    let res = panic::catch_unwind(|| {
+        let solver = solver_for_day(day, &path_to_input).unwrap();
+        let parsed = solver.parse();
+        if !errors.is_empty() {
+            return;
+        }
+        if let Err(error) = parsed {
+            panic!("the checker accepts an input the parser rejects: {}", error);
+        }
+        // Overflows come back as errors, anything else still panics
+        let _ = try_solve_part(solver.as_ref(), day, 1);
+        let _ = try_solve_part(solver.as_ref(), day, 2);
+    });
+    let _ = fs::remove_file(&path);
+    if let Err(payload) = res {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Runs `fuzz_day` on its own thread so hangs can be told apart from panics.
+pub fn run_input(day: u32, data: &[u8]) -> Result<(), Failure> {
+    let (tx, rx) = mpsc::channel();
+    let data = data.to_vec();
+    thread::spawn(move || {
+        let res = panic::catch_unwind(|| fuzz_day(day, &data));
+        let _ = tx.send(res.map_err(|payload| panic_message(payload.as_ref())));
+    });
+    match rx.recv_timeout(TIMEOUT) {
+        Ok(res) => res.map_err(Failure::Panic),
+        // The hung thread is left behind, there is no way to stop it
+        Err(_) => Err(Failure::Hang),
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// How many lines of each stored input make it into the seed corpus. Mutating
+/// one of a thousand lines finds nothing a short input wouldn't, only slower.
+const SEED_LINES: usize = 20;
+
+/// The first lines of every stored input of `day`, full and sample ones.
+pub fn seed_corpus(day: u32) -> Vec<Vec<u8>> {
+    let prefix = format!("day{}", day);
+    let Ok(entries) = fs::read_dir(&config::current().inputs) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy();
+            // day1 must not pick up day10
+            name.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.starts_with(['.', '_']))
+                && name.ends_with(".input")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|p| fs::read(p).ok())
+        .map(|input| {
+            let lines: Vec<&[u8]> = input.split(|&b| b == b'\n').take(SEED_LINES).collect();
+            lines.join(&b'\n')
+        })
+        .collect()
+}
+
+/// Mutates the seed corpus of `day` for `iterations` rounds, stopping at the
+/// first crash, which is shrunk before it is returned.
+pub fn fuzz(day: u32, iterations: usize, seed: u64) -> Option<Crash> {
+    let corpus = seed_corpus(day);
+    if corpus.is_empty() {
+        return None;
+    }
+    let mut rng = Rng::new(seed);
+    for _ in 0..iterations {
+        let mut input = corpus[rng.below(corpus.len() as u64) as usize].clone();
+        for _ in 0..=rng.below(4) {
+            mutate(&mut rng, &mut input, &corpus);
+        }
+        if let Err(failure) = run_input(day, &input) {
+            // A hung thread keeps running, so hangs are not worth shrinking
+            let input = match failure {
+                Failure::Panic(_) => shrink(day, input),
+                Failure::Hang => input,
+            };
+            return Some(Crash {
+                day,
+                input,
+                failure,
+            });
+        }
+    }
+    None
+}
+
+fn mutate(rng: &mut Rng, input: &mut Vec<u8>, corpus: &[Vec<u8>]) {
+    const INTERESTING: &[u8] = b" \n\r:;,=()|-0123456789ABLRSZ.#*";
+    let len = input.len() as u64;
+    match rng.below(7) {
+        // Overwrite a byte
+        0 if len > 0 => {
+            let i = rng.below(len) as usize;
+            input[i] = rng.pick(INTERESTING);
+        }
+        // Insert a byte
+        1 => {
+            let i = rng.below(len + 1) as usize;
+            input.insert(i, rng.pick(INTERESTING));
+        }
+        // Delete a run of bytes
+        2 if len > 0 => {
+            let i = rng.below(len) as usize;
+            let n = 1 + rng.below(8) as usize;
+            input.drain(i..(i + n).min(input.len()));
+        }
+        // Truncate
+        3 => input.truncate(rng.below(len + 1) as usize),
+        // Drop or duplicate a whole line
+        4 | 5 => {
+            let mut lines: Vec<Vec<u8>> =
+                input.split(|&b| b == b'\n').map(|l| l.to_vec()).collect();
+            let i = rng.below(lines.len() as u64) as usize;
+            if rng.chance(50) {
+                lines.remove(i);
+            } else {
+                let line = lines[i].clone();
+                lines.insert(i, line);
+            }
+            *input = lines.join(&b'\n');
+        }
+        // Splice in a piece of another corpus entry
+        _ => {
+            let other = &corpus[rng.below(corpus.len() as u64) as usize];
+            if !other.is_empty() {
+                let start = rng.below(other.len() as u64) as usize;
+                let end = (start + 1 + rng.below(32) as usize).min(other.len());
+                let at = rng.below(len + 1) as usize;
+                input.splice(at..at, other[start..end].iter().copied());
+            }
+        }
+    }
+}
+
+/// Removes ever smaller chunks of `input` for as long as it keeps panicking.
+fn shrink(day: u32, mut input: Vec<u8>) -> Vec<u8> {
+    let mut chunk = input.len() / 2;
+    while chunk > 0 {
+        let mut start = 0;
+        while start < input.len() {
+            let mut candidate = input.clone();
+            candidate.drain(start..(start + chunk).min(input.len()));
+            if matches!(run_input(day, &candidate), Err(Failure::Panic(_))) {
+                input = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    input
+}
+
+/// Saves `crash` to `dir` as a regression input and returns its path.
+pub fn save_crash<P>(dir: P, crash: &Crash) -> io::Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let hash = crate::cache::input_hash(&crash.input);
+    let path = dir.join(format!("day{}-{:016x}.input", crash.day, hash));
+    fs::write(&path, &crash.input)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_corpus_keeps_days_apart() {
+        assert_eq!(seed_corpus(1).len(), 1);
+        assert_eq!(seed_corpus(10).len(), 3);
+        assert_eq!(seed_corpus(8).len(), 4);
+    }
+
+    #[test]
+    fn parse_steps_report_malformed_input() {
+        let path = std::env::temp_dir().join(format!("aoc-fuzz-parse-{}", std::process::id()));
+        let malformed = [
+            (3, "..\n...\n"),
+            (5, "seeds: 1\n\nseed-to-soil map:\n1 2\n"),
+            (6, "Time:\nDistance: 9\n"),
+            (7, "AK 5\n"),
+            (8, "LR\n\nAAA = BBB\n"),
+            (8, "\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n"),
+            (9, "1 x 3\n"),
+        ];
+        for (day, text) in malformed {
+            fs::write(&path, text).unwrap();
+            let solver = solver_for_day(day, &path.to_string_lossy()).unwrap();
+            assert!(solver.parse().is_err(), "day {}", day);
+            assert_eq!(run_input(day, text.as_bytes()), Ok(()), "day {}", day);
+        }
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn stored_inputs_do_not_crash() {
+        for day in 1..=crate::NUM_DAYS {
+            for input in seed_corpus(day) {
+                assert_eq!(run_input(day, &input), Ok(()), "day {}", day);
+            }
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! Random but well-formed puzzle inputs for stress testing the solvers.
+//!
+//! Every generator is driven by `Rng`, a small SplitMix64, so the same day,
+//! size and seed always produce the same input and a failure can be replayed
+//! from the three numbers alone. `size` loosely scales the input: lines for
+//! line-based days, the width of the grid for grid days.
+
+use crate::Day5;
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, `n` must not be 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Uniform in `lo..=hi`.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo + 1) as u64) as i64
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Generates an input for `day`, or `None` if there is no generator for it.
+pub fn generate_input(day: u32, size: usize, seed: u64) -> Option<String> {
+    let mut rng = Rng::new(seed);
+    let size = size.max(1);
+    let lines = match day {
+        1 => day1(&mut rng, size),
+        2 => day2(&mut rng, size),
+        3 => day3(&mut rng, size),
+        4 => day4(&mut rng, size),
+        5 => day5(&mut rng, size),
+        6 => day6(&mut rng, size),
+        7 => day7(&mut rng, size),
+        8 => day8(&mut rng, size),
+        9 => day9(&mut rng, size),
+        10 => day10(&mut rng, size),
+        _ => return None,
+    };
+    Some(lines.join("\n"))
+}
+
+pub(crate) const DIGIT_WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+// Calibration lines: letters with digits and spelled-out digits mixed in
+fn day1(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            let pieces = rng.range(1, 8);
+            for _ in 0..pieces {
+                match rng.below(3) {
+                    0 => line.push(char::from(b'1' + rng.below(9) as u8)),
+                    1 => line.push_str(rng.pick(&DIGIT_WORDS)),
+                    _ => line.push(char::from(b'a' + rng.below(26) as u8)),
+                }
+            }
+            // Part 1 needs at least one real digit on every line
+            if !line.chars().any(|c| c.is_ascii_digit()) {
+                let at = rng.below(line.len() as u64 + 1) as usize;
+                line.insert(at, char::from(b'1' + rng.below(9) as u8));
+            }
+            line
+        })
+        .collect()
+}
+
+fn day2(rng: &mut Rng, size: usize) -> Vec<String> {
+    (1..=size)
+        .map(|id| {
+            let rounds: Vec<String> = (0..rng.range(1, 6))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    let num_colors = rng.range(1, 3) as usize;
+                    colors[..num_colors]
+                        .iter()
+                        .map(|color| format!("{} {}", rng.range(1, 15), color))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect();
+            format!("Game {}: {}", id, rounds.join("; "))
+        })
+        .collect()
+}
+
+// Engine schematic: numbers and symbols scattered over dots
+fn day3(rng: &mut Rng, size: usize) -> Vec<String> {
+    let width = size.max(3);
+    let symbols = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+    (0..width)
+        .map(|_| {
+            let mut row = String::new();
+            while row.len() < width {
+                let len = rng.range(1, 3) as usize;
+                if rng.chance(20) && row.len() + len <= width {
+                    let lowest = 10i64.pow(len as u32 - 1);
+                    row.push_str(&rng.range(lowest, lowest * 10 - 1).to_string());
+                    // Keep numbers from running into each other
+                    if row.len() < width {
+                        row.push('.');
+                    }
+                } else if rng.chance(12) {
+                    row.push(rng.pick(&symbols));
+                } else {
+                    row.push('.');
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+fn distinct_numbers(rng: &mut Rng, count: usize, max: i64) -> Vec<i64> {
+    let mut numbers: Vec<i64> = (1..=max).collect();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(count);
+    numbers
+}
+
+fn day4(rng: &mut Rng, size: usize) -> Vec<String> {
+    let format_numbers = |numbers: Vec<i64>| -> String {
+        numbers
+            .iter()
+            .map(|n| format!("{:>2}", n))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    (1..=size)
+        .map(|id| {
+            let winning = distinct_numbers(rng, 5, 99);
+            // Mix some winners into the numbers we have so cards actually score
+            let mut have = distinct_numbers(rng, 8, 99);
+            for n in &mut have {
+                if rng.chance(25) {
+                    *n = rng.pick(&winning);
+                }
+            }
+            have.sort();
+            have.dedup();
+            rng.shuffle(&mut have);
+            format!(
+                "Card {:>3}: {} | {}",
+                id,
+                format_numbers(winning),
+                format_numbers(have)
+            )
+        })
+        .collect()
+}
+
+// Almanac whose maps have non-overlapping source ranges
+fn day5(rng: &mut Rng, size: usize) -> Vec<String> {
+    let space = 1_000_000i64;
+    let seeds: Vec<String> = (0..size.max(1))
+        .flat_map(|_| [rng.range(0, space - 1), rng.range(1, 1000)])
+        .map(|n| n.to_string())
+        .collect();
+    let mut lines = vec![format!("seeds: {}", seeds.join(" "))];
+    for name in Day5::MAP_NAMES {
+        lines.push(String::new());
+        lines.push(format!("{} map:", name));
+        let num_ranges = rng.range(1, size as i64 + 1) as usize;
+        // Cut the space into slots and put at most one range in each
+        let slot = space / num_ranges as i64;
+        let mut ranges = Vec::new();
+        for i in 0..num_ranges as i64 {
+            let len = rng.range(1, slot);
+            let source = i * slot + rng.range(0, slot - len);
+            let dest = rng.range(0, space - len);
+            ranges.push(format!("{} {} {}", dest, source, len));
+        }
+        rng.shuffle(&mut ranges);
+        lines.extend(ranges);
+    }
+    lines
+}
+
+fn day6(rng: &mut Rng, size: usize) -> Vec<String> {
+    let mut times = Vec::new();
+    let mut distances = Vec::new();
+    for _ in 0..size {
+        let time = rng.range(5, 99);
+        // Leave at least one way to beat the record
+        let best = (time / 2) * (time - time / 2);
+        times.push(time);
+        distances.push(rng.range(1, best - 1));
+    }
+    let width = 1 + distances.iter().map(|d| d.to_string().len()).max().unwrap_or(1);
+    let row = |label: &str, numbers: &[i64]| -> String {
+        let cells: String = numbers.iter().map(|n| format!("{:>width$}", n)).collect();
+        format!("{:<9}{}", label, cells)
+    };
+    vec![row("Time:", &times), row("Distance:", &distances)]
+}
+
+fn day7(rng: &mut Rng, size: usize) -> Vec<String> {
+    let cards = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+    (0..size)
+        .map(|_| {
+            // Draw from a few ranks at a time so pairs and full houses turn up
+            let ranks: Vec<char> = (0..rng.range(1, 5)).map(|_| rng.pick(&cards)).collect();
+            let hand: String = (0..5).map(|_| rng.pick(&ranks)).collect();
+            format!("{} {}", hand, rng.range(1, 1000))
+        })
+        .collect()
+}
+
+// A network where every ..A node walks a cycle that passes through one ..Z node,
+// AAA and ZZZ among them, plus unreachable junk nodes
+fn day8(rng: &mut Rng, size: usize) -> Vec<String> {
+    let directions: String = (0..size.max(1))
+        .map(|_| if rng.chance(50) { 'L' } else { 'R' })
+        .collect();
+    let mut used = std::collections::HashSet::<String>::new();
+    used.insert("AAA".to_string());
+    used.insert("ZZZ".to_string());
+    let mut name = |rng: &mut Rng, last: Option<char>| -> String {
+        loop {
+            let letter = |rng: &mut Rng| char::from(b'B' + rng.below(24) as u8);
+            let n: String = [letter(rng), letter(rng), last.unwrap_or_else(|| letter(rng))]
+                .iter()
+                .collect();
+            if used.insert(n.clone()) {
+                return n;
+            }
+        }
+    };
+
+    let mut nodes = Vec::<(String, String, String)>::new();
+    let num_ghosts = 1 + size / 4;
+    for ghost in 0..num_ghosts.min(6) {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_string(), "ZZZ".to_string())
+        } else {
+            (name(rng, Some('A')), name(rng, Some('Z')))
+        };
+        let len = rng.range(2, size as i64 + 2) as usize;
+        let mut chain = vec![start];
+        chain.extend((1..len).map(|_| name(rng, None)));
+        chain.push(end);
+        for pair in chain.windows(2) {
+            nodes.push((pair[0].clone(), pair[1].clone(), pair[1].clone()));
+        }
+        // ..Z leads back into the cycle, so ..Z is reached every `len` steps
+        let end = chain.last().unwrap().clone();
+        nodes.push((end, chain[1].clone(), chain[1].clone()));
+    }
+    let junk: Vec<String> = (0..size).map(|_| name(rng, None)).collect();
+    for node in &junk {
+        let left = &junk[rng.below(junk.len() as u64) as usize];
+        let right = &junk[rng.below(junk.len() as u64) as usize];
+        nodes.push((node.clone(), left.clone(), right.clone()));
+    }
+    rng.shuffle(&mut nodes);
+
+    let mut lines = vec![directions, String::new()];
+    lines.extend(
+        nodes
+            .into_iter()
+            .map(|(node, left, right)| format!("{} = ({}, {})", node, left, right)),
+    );
+    lines
+}
+
+// Histories sampled from low-degree polynomials, so the differences reach zero
+fn day9(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let coefficients: Vec<i64> = (0..=rng.range(0, 3)).map(|_| rng.range(-5, 5)).collect();
+            let len = rng.range(5, 21);
+            (0..len)
+                .map(|x| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, c| acc * x + c)
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+// A single closed pipe loop with junk pipes around it. The loop is the outline
+// of a random histogram of cells, drawn on a grid twice as fine so the outline
+// itself runs through cells.
+fn day10(rng: &mut Rng, size: usize) -> Vec<String> {
+    let columns = (size / 2).max(1);
+    let rows = (size / 2).max(1);
+    let heights: Vec<usize> = (0..columns).map(|_| rng.range(1, rows as i64) as usize).collect();
+    let filled = |x: i64, y: i64| -> bool {
+        x >= 0
+            && y >= 0
+            && (x as usize) < columns
+            && (y as usize) < rows
+            && y as usize >= rows - heights[x as usize]
+    };
+    let margin_x = rng.range(0, 2) as usize;
+    let margin_y = rng.range(0, 2) as usize;
+    let width = 2 * columns + 1 + margin_x + rng.range(0, 2) as usize;
+    let height = 2 * rows + 1 + margin_y + rng.range(0, 2) as usize;
+
+    // Loop cells sit on the outline: lattice points and the edges between them
+    let on_loop = |fx: usize, fy: usize| -> bool {
+        if fx < margin_x || fy < margin_y {
+            return false;
+        }
+        let (fx, fy) = ((fx - margin_x) as i64, (fy - margin_y) as i64);
+        let around = |x: i64, y: i64| {
+            [filled(x - 1, y - 1), filled(x, y - 1), filled(x - 1, y), filled(x, y)]
+        };
+        match (fx % 2, fy % 2) {
+            (0, 0) => {
+                let cells = around(fx / 2, fy / 2);
+                cells.iter().any(|&c| c) && !cells.iter().all(|&c| c)
+            }
+            (0, 1) => filled(fx / 2 - 1, fy / 2) != filled(fx / 2, fy / 2),
+            (1, 0) => filled(fx / 2, fy / 2 - 1) != filled(fx / 2, fy / 2),
+            _ => false,
+        }
+    };
+    let mut grid = vec![vec!['.'; width]; height];
+    let mut loop_cells = Vec::new();
+    for (y, row) in grid.iter_mut().enumerate() {
+        for (x, tile) in row.iter_mut().enumerate() {
+            if !on_loop(x, y) {
+                if rng.chance(40) {
+                    *tile = rng.pick(&['|', '-', 'L', 'J', '7', 'F']);
+                }
+                continue;
+            }
+            let north = y > 0 && on_loop(x, y - 1);
+            let south = on_loop(x, y + 1);
+            let west = x > 0 && on_loop(x - 1, y);
+            let east = on_loop(x + 1, y);
+            *tile = match (north, south, west, east) {
+                (true, true, false, false) => '|',
+                (false, false, true, true) => '-',
+                (true, false, false, true) => 'L',
+                (true, false, true, false) => 'J',
+                (false, true, true, false) => '7',
+                (false, true, false, true) => 'F',
+                _ => unreachable!("outline of a histogram never branches"),
+            };
+            loop_cells.push((x, y));
+        }
+    }
+
+    let (sx, sy) = rng.pick(&loop_cells);
+    grid[sy][sx] = 'S';
+    // Junk next to S must not look like it connects, or S would have more than two exits
+    let neighbours = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+    for (dx, dy) in neighbours {
+        let (x, y) = (sx as i64 + dx, sy as i64 + dy);
+        let inside = x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height;
+        if inside && !on_loop(x as usize, y as usize) {
+            grid[y as usize][x as usize] = '.';
+        }
+    }
+    grid.into_iter().map(|row| row.into_iter().collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::check_lines;
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=crate::NUM_DAYS {
+            assert_eq!(generate_input(day, 12, 7), generate_input(day, 12, 7));
+            assert_ne!(generate_input(day, 12, 7), generate_input(day, 12, 8));
+        }
+        assert_eq!(generate_input(crate::NUM_DAYS + 1, 12, 7), None);
+    }
+
+    #[test]
+    fn generated_inputs_pass_the_checker() {
+        for day in 1..=crate::NUM_DAYS {
+            for seed in 0..20 {
+                let input = generate_input(day, 1 + seed as usize, seed).unwrap();
+                let lines: Vec<&str> = input.lines().collect();
+                let errors = check_lines(day, &lines).unwrap();
+                assert!(errors.is_empty(), "day {} seed {}: {:?}\n{}", day, seed, errors, input);
+            }
+        }
+    }
+}
@@ -0,0 +1,430 @@
+//! Graph searches shared by the days.
+//!
+//! Every algorithm works on a `Graph`, either an `AdjacencyList` built up
+//! front or an `Implicit` graph whose edges come from a closure, which suits
+//! grids and node maps that are cheaper to ask than to copy. Edges carry a
+//! weight; the unweighted searches count every edge as one step. Cycle
+//! detection and `steps_until` work on a step function instead, for walks
+//! that only ever have one way to go.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    /// The nodes one edge away from `node`, with the weight of each edge.
+    fn neighbours(&self, node: &Self::Node) -> Vec<(Self::Node, u64)>;
+}
+
+#[derive(Debug, Clone)]
+pub struct AdjacencyList<N> {
+    edges: HashMap<N, Vec<(N, u64)>>,
+}
+
+impl<N: Clone + Eq + Hash> Default for AdjacencyList<N> {
+    fn default() -> Self {
+        Self {
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> AdjacencyList<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.edges.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: u64) {
+        self.add_node(to.clone());
+        self.edges.entry(from).or_default().push((to, weight));
+    }
+
+    /// An edge both ways.
+    pub fn add_undirected_edge(&mut self, a: N, b: N, weight: u64) {
+        self.add_edge(a.clone(), b.clone(), weight);
+        self.add_edge(b, a, weight);
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph for AdjacencyList<N> {
+    type Node = N;
+
+    fn neighbours(&self, node: &N) -> Vec<(N, u64)> {
+        self.edges.get(node).cloned().unwrap_or_default()
+    }
+}
+
+type Neighbours<'a, N> = Box<dyn Fn(&N) -> Vec<(N, u64)> + 'a>;
+
+pub struct Implicit<'a, N> {
+    neighbours: Neighbours<'a, N>,
+}
+
+impl<'a, N> Implicit<'a, N> {
+    pub fn new(neighbours: impl Fn(&N) -> Vec<(N, u64)> + 'a) -> Self {
+        Self {
+            neighbours: Box::new(neighbours),
+        }
+    }
+
+    /// A graph where every edge weighs 1.
+    pub fn unweighted(neighbours: impl Fn(&N) -> Vec<N> + 'a) -> Self {
+        Self::new(move |node| neighbours(node).into_iter().map(|n| (n, 1)).collect())
+    }
+}
+
+impl<N: Clone + Eq + Hash> Graph for Implicit<'_, N> {
+    type Node = N;
+
+    fn neighbours(&self, node: &N) -> Vec<(N, u64)> {
+        (self.neighbours)(node)
+    }
+}
+
+/// The number of edges on a shortest path from `start` to every node it reaches.
+pub fn bfs<G: Graph>(graph: &G, start: G::Node) -> HashMap<G::Node, usize> {
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for (next, _) in graph.neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// The depth at which a depth-first search from `start` first finds each node.
+pub fn dfs<G: Graph>(graph: &G, start: G::Node) -> HashMap<G::Node, usize> {
+    let mut depths = HashMap::new();
+    let mut stack = vec![(start, 0)];
+    while let Some((node, depth)) = stack.pop() {
+        if depths.contains_key(&node) {
+            continue;
+        }
+        depths.insert(node.clone(), depth);
+        // Reversed so the first neighbour is explored first
+        for (next, _) in graph.neighbours(&node).into_iter().rev() {
+            if !depths.contains_key(&next) {
+                stack.push((next, depth + 1));
+            }
+        }
+    }
+    depths
+}
+
+/// The total weight of a lightest path from `start` to every node it reaches.
+pub fn dijkstra<G: Graph>(graph: &G, start: G::Node) -> HashMap<G::Node, u64> {
+    let mut distances = HashMap::new();
+    // Nodes need not be ordered, so the heap holds indices into `seen` instead
+    let mut seen = vec![start];
+    let mut heap = BinaryHeap::from([Reverse((0, 0))]);
+    while let Some(Reverse((distance, index))) = heap.pop() {
+        let node = seen[index].clone();
+        if distances.contains_key(&node) {
+            continue;
+        }
+        distances.insert(node.clone(), distance);
+        for (next, weight) in graph.neighbours(&node) {
+            if !distances.contains_key(&next) {
+                heap.push(Reverse((distance + weight, seen.len())));
+                seen.push(next);
+            }
+        }
+    }
+    distances
+}
+
+/// A lightest path from `start` to a node where `is_goal` holds, with its total
+/// weight. `heuristic` must never overestimate the weight left to a goal; with
+/// one that always returns 0 this is Dijkstra stopping at the first goal.
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::Node,
+    is_goal: impl Fn(&G::Node) -> bool,
+    heuristic: impl Fn(&G::Node) -> u64,
+) -> Option<(u64, Vec<G::Node>)> {
+    let mut best = HashMap::from([(start.clone(), 0)]);
+    let mut came_from = HashMap::<G::Node, G::Node>::new();
+    let mut seen = vec![start.clone()];
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0, 0))]);
+    while let Some(Reverse((_, distance, index))) = heap.pop() {
+        let node = seen[index].clone();
+        if best.get(&node).is_some_and(|&b| b < distance) {
+            continue;
+        }
+        if is_goal(&node) {
+            let mut path = vec![node];
+            while let Some(prev) = came_from.get(path.last().unwrap()) {
+                path.push(prev.clone());
+            }
+            path.reverse();
+            return Some((distance, path));
+        }
+        for (next, weight) in graph.neighbours(&node) {
+            let next_distance = distance + weight;
+            if best.get(&next).is_none_or(|&b| next_distance < b) {
+                best.insert(next.clone(), next_distance);
+                came_from.insert(next.clone(), node.clone());
+                heap.push(Reverse((
+                    next_distance + heuristic(&next),
+                    next_distance,
+                    seen.len(),
+                )));
+                seen.push(next);
+            }
+        }
+    }
+    None
+}
+
+/// Groups `nodes` by what they can reach. Edges are followed the way they point,
+/// so for a directed graph only edges added both ways join two nodes.
+pub fn connected_components<G: Graph>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = G::Node>,
+) -> Vec<Vec<G::Node>> {
+    let mut assigned = HashSet::new();
+    let mut components = Vec::new();
+    for node in nodes {
+        if assigned.contains(&node) {
+            continue;
+        }
+        let mut component = Vec::new();
+        let mut queue = VecDeque::from([node.clone()]);
+        assigned.insert(node);
+        while let Some(node) = queue.pop_front() {
+            for (next, _) in graph.neighbours(&node) {
+                if assigned.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+            component.push(node);
+        }
+        components.push(component);
+    }
+    components
+}
+
+/// `nodes` ordered so every edge points forwards, `None` when there is a cycle.
+/// Ties keep the order of `nodes`.
+pub fn topological_sort<G: Graph>(
+    graph: &G,
+    nodes: impl IntoIterator<Item = G::Node>,
+) -> Option<Vec<G::Node>> {
+    let nodes: Vec<G::Node> = nodes.into_iter().collect();
+    let mut incoming: HashMap<G::Node, usize> = nodes.iter().map(|n| (n.clone(), 0)).collect();
+    for node in &nodes {
+        for (next, _) in graph.neighbours(node) {
+            *incoming.entry(next).or_default() += 1;
+        }
+    }
+    let mut ready: VecDeque<G::Node> = nodes
+        .iter()
+        .filter(|n| incoming[*n] == 0)
+        .cloned()
+        .collect();
+    let mut sorted = Vec::with_capacity(incoming.len());
+    while let Some(node) = ready.pop_front() {
+        for (next, _) in graph.neighbours(&node) {
+            let count = incoming.get_mut(&next).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(next);
+            }
+        }
+        sorted.push(node);
+    }
+    (sorted.len() == incoming.len()).then_some(sorted)
+}
+
+/// Where the sequence `start, step(start), step(step(start)), ...` starts
+/// repeating and how long the repeating part is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Steps taken before entering the cycle
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Floyd's tortoise and hare: two walkers, one twice as fast as the other.
+pub fn floyd<T: Clone + Eq>(start: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+    let mut cycle_start = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Brent's algorithm, the same answer as `floyd` in fewer steps.
+pub fn brent<T: Clone + Eq>(start: T, step: impl Fn(&T) -> T) -> Cycle {
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+    let (mut tortoise, mut hare) = (start.clone(), start);
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// How many steps the walk from `start` takes until `done` holds, `None` when
+/// it goes round its cycle without that ever happening.
+pub fn steps_until<T: Clone + Eq>(
+    start: T,
+    step: impl Fn(&T) -> T,
+    done: impl Fn(&T) -> bool,
+) -> Option<usize> {
+    // Every state the walk ever visits is among the first `start + length`
+    let cycle = brent(start.clone(), &step);
+    std::iter::successors(Some(start), |state| Some(step(state)))
+        .take(cycle.start + cycle.length)
+        .position(|state| done(&state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 and a heavy shortcut 0 -> 3, plus 4 <-> 5 on their own
+    fn sample() -> AdjacencyList<u32> {
+        let mut graph = AdjacencyList::new();
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 1);
+        graph.add_edge(2, 3, 1);
+        graph.add_edge(0, 3, 10);
+        graph.add_undirected_edge(4, 5, 2);
+        graph
+    }
+
+    #[test]
+    fn searches_count_edges_or_weights() {
+        let graph = sample();
+        let hops = bfs(&graph, 0);
+        assert_eq!((hops[&3], hops.len()), (1, 4));
+        let depths = dfs(&graph, 0);
+        assert_eq!(depths[&3], 3);
+        let weights = dijkstra(&graph, 0);
+        assert_eq!(weights[&3], 3);
+        assert!(!weights.contains_key(&4));
+        let (weight, path) = astar(&graph, 0, |&n| n == 3, |_| 0).unwrap();
+        assert_eq!((weight, path), (3, vec![0, 1, 2, 3]));
+        assert_eq!(astar(&graph, 0, |&n| n == 5, |_| 0), None);
+    }
+
+    #[test]
+    fn astar_on_an_implicit_grid_matches_bfs() {
+        // An open 20x20 grid with a wall at x == 10 that has a gap at y == 17
+        let open = |(x, y): (i32, i32)| {
+            (0..20).contains(&x) && (0..20).contains(&y) && (x != 10 || y == 17)
+        };
+        let grid = Implicit::unweighted(|&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|&p| open(p))
+                .collect()
+        });
+        let goal = (19, 0);
+        let manhattan = |&(x, y): &(i32, i32)| {
+            (goal.0 - x).unsigned_abs() as u64 + (goal.1 - y).unsigned_abs() as u64
+        };
+        let (weight, path) = astar(&grid, (0, 0), |&p| p == goal, manhattan).unwrap();
+        assert_eq!(weight as usize, bfs(&grid, (0, 0))[&goal]);
+        assert_eq!(path.len(), weight as usize + 1);
+        assert!(path.contains(&(10, 17)));
+    }
+
+    #[test]
+    fn finds_components_and_orders() {
+        let graph = sample();
+        let mut nodes: Vec<u32> = graph.nodes().copied().collect();
+        nodes.sort();
+        let components = connected_components(&graph, nodes.clone());
+        assert_eq!(components, vec![vec![0, 1, 3, 2], vec![4, 5]]);
+        assert_eq!(topological_sort(&graph, 0..4), Some(vec![0, 1, 2, 3]));
+        // 4 and 5 point at each other
+        assert_eq!(topological_sort(&graph, nodes), None);
+    }
+
+    #[test]
+    fn cycle_detection_matches_brute_force() {
+        for modulus in 1..40u64 {
+            for offset in 0..5 {
+                // x -> x^2 + offset mod m is eventually periodic from any start
+                let step = |&x: &u64| (x * x + offset) % modulus;
+                let mut seen = HashMap::new();
+                let mut x = 3 % modulus;
+                let mut i = 0;
+                while !seen.contains_key(&x) {
+                    seen.insert(x, i);
+                    x = step(&x);
+                    i += 1;
+                }
+                let expected = Cycle {
+                    start: seen[&x],
+                    length: i - seen[&x],
+                };
+                assert_eq!(floyd(3 % modulus, step), expected, "mod {}", modulus);
+                assert_eq!(brent(3 % modulus, step), expected, "mod {}", modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn steps_until_stops_once_the_walk_repeats() {
+        // 0 -> 1 -> ... -> 9 -> 5 -> 6 -> ...
+        let step = |&x: &u32| if x == 9 { 5 } else { x + 1 };
+        assert_eq!(steps_until(0, step, |&x| x == 0), Some(0));
+        assert_eq!(steps_until(0, step, |&x| x == 7), Some(7));
+        assert_eq!(steps_until(0, step, |&x| x > 9), None);
+    }
+}
@@ -0,0 +1,372 @@
+//! Rectangular grids of cells addressed by `Point`s.
+//!
+//! Points use signed coordinates with `y` growing downwards, the way puzzle
+//! inputs are read, so stepping off an edge gives a point that simply isn't in
+//! the grid instead of an underflow. `Grid::get` is the bounds check.
+
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The neighbouring point in `direction`.
+    pub fn step(self, direction: Direction) -> Self {
+        self + direction.offset()
+    }
+
+    /// The direction from `self` to `other` when they are neighbours.
+    pub fn direction_to(self, other: Point) -> Option<Direction> {
+        let offset = other - self;
+        Direction::ALL.into_iter().find(|d| d.offset() == offset)
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+/// Compass directions, clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    pub fn offset(self) -> Point {
+        let (x, y) = match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        };
+        Point::new(x, y)
+    }
+
+    /// Turns clockwise by `eighths` eighths of a full turn, anticlockwise when negative.
+    pub fn rotate(self, eighths: i32) -> Self {
+        let index = Self::ALL.iter().position(|&d| d == self).unwrap() as i32;
+        Self::ALL[(index + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(-2)
+    }
+
+    pub fn reverse(self) -> Self {
+        self.rotate(4)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    /// Row by row
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// `None` when the rows are not all as wide as the first one.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        let height = rows.len();
+        Some(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0..self.width as i64).contains(&p.x) && (0..self.height as i64).contains(&p.y)
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        self.contains(p)
+            .then(|| p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.index_of(p).map(|i| &mut self.cells[i])
+    }
+
+    /// Every point of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width as i64;
+        (0..self.height as i64).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(&self.cells)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on 0, and a grid without columns has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "column {} is outside of the {}x{} grid",
+            x, self.width, self.height
+        );
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    /// The cells from `top_left` to `bottom_right`, both included, clipped to the grid.
+    pub fn region(
+        &self,
+        top_left: Point,
+        bottom_right: Point,
+    ) -> impl Iterator<Item = (Point, &T)> {
+        (top_left.y..=bottom_right.y)
+            .flat_map(move |y| (top_left.x..=bottom_right.x).map(move |x| Point::new(x, y)))
+            .filter_map(|p| self.get(p).map(|cell| (p, cell)))
+    }
+
+    /// The neighbours of `p` in `directions` that are in the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        p: Point,
+        directions: &'a [Direction],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        directions.iter().filter_map(move |&d| {
+            let next = p.step(d);
+            self.get(next).map(|cell| (next, cell))
+        })
+    }
+
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(p, &Direction::ORTHOGONAL)
+    }
+
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(p, &Direction::ALL)
+    }
+
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Point> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(p, _)| p)
+    }
+
+    pub fn find_all(&self, mut predicate: impl FnMut(&T) -> bool) -> Vec<Point> {
+        self.iter()
+            .filter(|(_, cell)| predicate(cell))
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    /// One row per line of `text`, `None` when the lines differ in length.
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::from_rows(crate::input::grid(text))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.get(p) {
+            Some(cell) => cell,
+            None => panic!(
+                "{:?} is outside of the {}x{} grid",
+                p, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(p) {
+            Some(cell) => cell,
+            None => panic!("{:?} is outside of the {}x{} grid", p, width, height),
+        }
+    }
+}
+
+/// The cells row by row, with nothing between the cells of a row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse("ab.\n.#c\n").unwrap()
+    }
+
+    #[test]
+    fn directions_turn_and_reverse() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::SouthEast.reverse(), Direction::NorthWest);
+        assert_eq!(Direction::West.rotate(1), Direction::NorthWest);
+        assert_eq!(Direction::North.rotate(-9), Direction::NorthWest);
+        let p = Point::new(3, 3);
+        for d in Direction::ALL {
+            assert_eq!(p.step(d).step(d.reverse()), p);
+            assert_eq!(p.direction_to(p.step(d)), Some(d));
+        }
+        assert_eq!(p.direction_to(Point::new(5, 3)), None);
+    }
+
+    #[test]
+    fn neighbours_stay_in_the_grid() {
+        let grid = sample();
+        let corner: Vec<char> = grid
+            .neighbours4(Point::new(0, 0))
+            .map(|(_, &c)| c)
+            .collect();
+        assert_eq!(corner, ['b', '.']);
+        assert_eq!(grid.neighbours8(Point::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbours8(Point::new(1, 1)).count(), 5);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid[Point::new(2, 1)], 'c');
+    }
+
+    #[test]
+    fn iterates_rows_columns_and_regions() {
+        let grid = sample();
+        assert_eq!(grid.row(1), ['.', '#', 'c']);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(2).collect::<String>(), ".c");
+        let region: String = grid
+            .region(Point::new(1, -1), Point::new(3, 0))
+            .map(|(_, &c)| c)
+            .collect();
+        assert_eq!(region, "b.");
+        assert_eq!(grid.find_all(|c| c.is_alphabetic()).len(), 3);
+        assert_eq!(grid.find(|&c| c == '#'), Some(Point::new(1, 1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 is outside of the 3x2 grid")]
+    fn columns_past_the_right_edge_panic() {
+        // Skipping past the width would otherwise start on the next row
+        sample().column(3).count();
+    }
+
+    #[test]
+    fn displays_and_maps_cells() {
+        let mut grid = sample();
+        assert_eq!(grid.to_string(), "ab.\n.#c");
+        grid[Point::new(0, 1)] = 'z';
+        let walls = grid.map(|&c| u8::from(c == '#'));
+        assert_eq!(walls.to_string(), "000\n010");
+        assert!(Grid::parse("ab\nc\n").is_none());
+    }
+}
@@ -0,0 +1,312 @@
+//! A log of every part the runner solved, and the trends in it.
+//!
+//! Each run appends one line to the history file holding the time in seconds
+//! since the epoch, day, part, answer, duration in nanoseconds, input hash and
+//! the git commit of the working tree, with `-dirty` when it has uncommitted
+//! changes, separated by tabs:
+//!
+//! ```text
+//! 1760870400 6 2 Int32 29891250 2184113207 3c9a41e0b5f2d7a8 a2f137d6e1b0
+//! ```
+//!
+//! Answers from the result cache are not runs and aren't logged. Only the
+//! binary turns the log on, so tests and other users of the library leave no
+//! trail.
+
+use crate::aoc::AocRes;
+use crate::cache::{decode, encode};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_HISTORY_FILE: &str = "./.aoc-history";
+
+/// Sparklines show this many of the latest runs.
+pub const WIDTH: usize = 60;
+
+/// Sparkline levels, lowest first.
+const RAMP: &[u8] = b"_.,-=+*#";
+
+static LOG: RwLock<Option<PathBuf>> = RwLock::new(None);
+static COMMIT: OnceLock<String> = OnceLock::new();
+
+/// Starts appending runs to `path`, or stops with `None`.
+pub fn set_log(path: Option<PathBuf>) {
+    *LOG.write().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Seconds since the epoch
+    pub time: u64,
+    pub day: u32,
+    pub part: u32,
+    pub answer: AocRes,
+    pub duration: Duration,
+    pub input_hash: u64,
+    pub commit: String,
+}
+
+impl Entry {
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{:016x}\t{}",
+            self.time,
+            self.day,
+            self.part,
+            encode(self.answer),
+            self.duration.as_nanos(),
+            self.input_hash,
+            self.commit
+        )
+    }
+
+    pub fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [time, day, part, answer, duration, input_hash, commit] = fields.as_slice() else {
+            return None;
+        };
+        Some(Self {
+            time: time.parse().ok()?,
+            day: day.parse().ok()?,
+            part: part.parse().ok()?,
+            answer: decode(answer)?,
+            duration: Duration::from_nanos(duration.parse().ok()?),
+            input_hash: u64::from_str_radix(input_hash, 16).ok()?,
+            commit: commit.to_string(),
+        })
+    }
+}
+
+/// Logs a run when the log is on, complaining on stderr if that fails.
+pub fn record(day: u32, part: u32, answer: AocRes, duration: Duration, input_hash: u64) {
+    let Some(path) = LOG.read().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return;
+    };
+    let entry = Entry {
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        day,
+        part,
+        answer,
+        duration,
+        input_hash,
+        commit: COMMIT.get_or_init(current_commit).clone(),
+    };
+    if let Err(e) = append(&path, &entry) {
+        eprintln!(
+            "could not log day{} part{} to {}: {}",
+            day,
+            part,
+            path.display(),
+            e
+        );
+    }
+}
+
+// Asked once per process, runs don't change the tree
+fn current_commit() -> String {
+    let git = |args: &[&str]| {
+        let output = Command::new("git").args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let Some(commit) = git(&["rev-parse", "--short=12", "HEAD"]) else {
+        return "unknown".to_string();
+    };
+    match git(&["status", "--porcelain", "--untracked-files=no"]) {
+        Some(changes) if changes.is_empty() => commit,
+        _ => commit + "-dirty",
+    }
+}
+
+pub fn append(path: &Path, entry: &Entry) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", entry.to_line())
+}
+
+/// Every entry in the log at `path`, oldest first. Lines that don't parse are
+/// skipped, a run cut short while writing shouldn't hide the rest.
+pub fn read(path: &Path) -> io::Result<Vec<Entry>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents.lines().filter_map(Entry::from_line).collect())
+}
+
+/// `values` as one character each, from `_` for the smallest to `#` for the largest.
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            let level = if max > min {
+                ((v - min) / (max - min) * (RAMP.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            RAMP[level] as char
+        })
+        .collect()
+}
+
+fn answer_value(answer: AocRes) -> f64 {
+    match answer {
+        AocRes::Int32(v) => v as f64,
+        AocRes::Int64(v) => v as f64,
+        AocRes::UInt32(v) => v as f64,
+        AocRes::UInt64(v) => v as f64,
+    }
+}
+
+/// A run whose answer differs from the one before it on the same input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<'a> {
+    pub before: &'a Entry,
+    pub after: &'a Entry,
+}
+
+/// The changes of answer in `runs`, which should all be of one part.
+pub fn changes<'a>(runs: &[&'a Entry]) -> Vec<Change<'a>> {
+    let mut last = HashMap::<u64, &Entry>::new();
+    let mut changes = Vec::new();
+    for &run in runs {
+        if let Some(before) = last.insert(run.input_hash, run) {
+            if before.answer != run.answer {
+                changes.push(Change { before, after: run });
+            }
+        }
+    }
+    changes
+}
+
+/// The trend of every part in `entries`, restricted to `day` and `part` when
+/// given, and whether any answer changed for the same input.
+pub fn report(entries: &[Entry], day: Option<u32>, part: Option<u32>) -> (String, bool) {
+    let mut parts: Vec<(u32, u32)> = entries
+        .iter()
+        .filter(|e| day.is_none_or(|d| d == e.day) && part.is_none_or(|p| p == e.part))
+        .map(|e| (e.day, e.part))
+        .collect();
+    parts.sort();
+    parts.dedup();
+
+    let mut out = String::new();
+    let mut changed = false;
+    for (day, part) in parts {
+        let runs: Vec<&Entry> = entries
+            .iter()
+            .filter(|e| e.day == day && e.part == part)
+            .collect();
+        let recent = &runs[runs.len().saturating_sub(WIDTH)..];
+        let durations: Vec<Duration> = recent.iter().map(|e| e.duration).collect();
+        let seconds: Vec<f64> = durations.iter().map(Duration::as_secs_f64).collect();
+        let answers: Vec<f64> = recent.iter().map(|e| answer_value(e.answer)).collect();
+        let last = runs[runs.len() - 1];
+        let fastest = durations.iter().min().unwrap();
+        let slowest = durations.iter().max().unwrap();
+
+        writeln!(out, "day{} - part{}: {} run(s)", day, part, runs.len()).unwrap();
+        writeln!(
+            out,
+            "  runtime {}  {:.3?} to {:.3?}, last {:.3?}",
+            sparkline(&seconds),
+            fastest,
+            slowest,
+            last.duration
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  answer  {}  last {}",
+            sparkline(&answers),
+            last.answer
+        )
+        .unwrap();
+        for change in changes(&runs) {
+            changed = true;
+            writeln!(
+                out,
+                "  CHANGED for input {:016x}: {} at {}, then {} at {}",
+                change.after.input_hash,
+                change.before.answer,
+                change.before.commit,
+                change.after.answer,
+                change.after.commit
+            )
+            .unwrap();
+        }
+    }
+    (out, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(part: u32, answer: i32, millis: u64, input_hash: u64, commit: &str) -> Entry {
+        Entry {
+            time: 1760870400,
+            day: 6,
+            part,
+            answer: AocRes::Int32(answer),
+            duration: Duration::from_millis(millis),
+            input_hash,
+            commit: commit.to_string(),
+        }
+    }
+
+    #[test]
+    fn appends_and_reads_back_entries() {
+        let path =
+            std::env::temp_dir().join(format!("aoc-history-test-{}/log", std::process::id()));
+        let first = entry(1, 2612736, 3, 0x3c9a41e0b5f2d7a8, "a2f137d6e1b0");
+        let second = entry(2, 29891250, 2184, 0x3c9a41e0b5f2d7a8, "a2f137d6e1b0-dirty");
+        append(&path, &first).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "cut short\t6\n").unwrap();
+        append(&path, &second).unwrap();
+        assert_eq!(read(&path).unwrap(), [first, second]);
+        assert_eq!(
+            Entry::from_line("1760870400\t6\t2\tInt32 29891250\t2184000000\t3c9a41e0b5f2d7a8\tabc"),
+            Some(entry(2, 29891250, 2184, 0x3c9a41e0b5f2d7a8, "abc"))
+        );
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn draws_trends_and_flags_changed_answers() {
+        assert_eq!(sparkline(&[1.0, 2.0, 8.0, 4.5]), "_.#=");
+        assert_eq!(sparkline(&[5.0, 5.0]), "__");
+
+        let entries = [
+            entry(2, 10, 100, 1, "aaa"),
+            entry(2, 10, 800, 1, "bbb"),
+            entry(2, 99, 500, 2, "ccc"),
+            entry(2, 11, 100, 1, "ddd"),
+            entry(1, 7, 1, 1, "ddd"),
+        ];
+        let (out, changed) = report(&entries, Some(6), Some(2));
+        assert!(changed);
+        assert_eq!(
+            out,
+            "day6 - part2: 4 run(s)\n  runtime _#=_  100.000ms to 800.000ms, last 100.000ms\n  answer  __#_  last 11\n  CHANGED for input 0000000000000001: 10 at bbb, then 11 at ddd\n"
+        );
+        let (out, changed) = report(&entries, None, Some(1));
+        assert!(!changed);
+        assert!(out.starts_with("day6 - part1: 1 run(s)\n"));
+    }
+}
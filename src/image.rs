@@ -0,0 +1,266 @@
+//! Images of grid inputs, one colored square per cell.
+//!
+//! Days with a grid input can return a `CellImage` from `Aoc::image`, with each
+//! cell tagged by a category such as "counted number" or "main loop". It is
+//! written as PPM, PNG or SVG without any image library: PNG uses uncompressed
+//! deflate blocks, which is plenty for a 140x140 grid.
+
+use std::fmt::Write;
+use std::path::Path;
+
+pub struct CellImage {
+    pub width: usize,
+    pub height: usize,
+    /// Index into `legend` for every cell, row by row
+    pub cells: Vec<usize>,
+    pub legend: Vec<(&'static str, [u8; 3])>,
+}
+
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// Picks the format from the extension of `path`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// Pixels per cell side in raster images.
+const SCALE: usize = 4;
+
+impl CellImage {
+    pub fn new(width: usize, height: usize, legend: &[(&'static str, [u8; 3])]) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width * height],
+            legend: legend.to_vec(),
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, category: usize) {
+        self.cells[y * self.width + x] = category;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> usize {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.to_ppm(),
+            ImageFormat::Png => self.to_png(),
+            ImageFormat::Svg => self.to_svg().into_bytes(),
+        }
+    }
+
+    // RGB rows of the scaled up image
+    fn pixel_rows(&self) -> Vec<Vec<u8>> {
+        let mut rows = Vec::with_capacity(self.height * SCALE);
+        for y in 0..self.height {
+            let mut row = Vec::with_capacity(self.width * SCALE * 3);
+            for x in 0..self.width {
+                let (_, color) = self.legend[self.get(x, y)];
+                for _ in 0..SCALE {
+                    row.extend_from_slice(&color);
+                }
+            }
+            for _ in 0..SCALE {
+                rows.push(row.clone());
+            }
+        }
+        rows
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out =
+            format!("P6\n{} {}\n255\n", self.width * SCALE, self.height * SCALE).into_bytes();
+        for row in self.pixel_rows() {
+            out.extend(row);
+        }
+        out
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        for row in self.pixel_rows() {
+            // Filter type 0, the row as is
+            raw.push(0);
+            raw.extend(row);
+        }
+
+        let mut ihdr = Vec::new();
+        ihdr.extend(((self.width * SCALE) as u32).to_be_bytes());
+        ihdr.extend(((self.height * SCALE) as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression, filtering and no interlace
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// One square per cell and a legend under the grid.
+    pub fn to_svg(&self) -> String {
+        const CELL: usize = 10;
+        let legend_height = self.legend.len() * (CELL + 6) + 6;
+        let (w, h) = (self.width * CELL, self.height * CELL + legend_height);
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">"
+        )
+        .unwrap();
+        writeln!(out, "<rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>").unwrap();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (_, color) = self.legend[self.get(x, y)];
+                writeln!(
+                    out,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"{}\"/>",
+                    x * CELL,
+                    y * CELL,
+                    hex(color)
+                )
+                .unwrap();
+            }
+        }
+        for (i, (name, color)) in self.legend.iter().enumerate() {
+            let y = self.height * CELL + 6 + i * (CELL + 6);
+            writeln!(
+                out,
+                "<rect x=\"0\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"{}\"/>",
+                hex(*color)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{CELL}\">{}</text>",
+                CELL + 4,
+                y + CELL - 1,
+                name
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// A zlib stream of deflate blocks that store the data uncompressed
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        out.push(last as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> CellImage {
+        let mut image = CellImage::new(3, 2, &[("off", [0, 0, 0]), ("on", [255, 128, 0])]);
+        for y in 0..2 {
+            for x in 0..3 {
+                image.set(x, y, (x + y) % 2);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn writes_scaled_ppm() {
+        let ppm = checkerboard().to_ppm();
+        let header = format!("P6\n{} {}\n255\n", 3 * SCALE, 2 * SCALE);
+        assert!(ppm.starts_with(header.as_bytes()));
+        assert_eq!(ppm.len(), header.len() + 3 * SCALE * 2 * SCALE * 3);
+        // The second cell of the first row is "on"
+        let pixel = header.len() + SCALE * 3;
+        assert_eq!(ppm[pixel..pixel + 3], [255, 128, 0]);
+    }
+
+    #[test]
+    fn writes_well_formed_png() {
+        let png = checkerboard().to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        assert_eq!(width as usize, 3 * SCALE);
+    }
+
+    #[test]
+    fn writes_svg_with_a_legend() {
+        let svg = checkerboard().to_svg();
+        assert_eq!(svg.matches("<rect").count(), 1 + 6 + 2);
+        assert!(svg.contains("#ff8000"));
+        assert!(svg.contains(">on</text>"));
+        assert!(matches!(
+            ImageFormat::from_path("out/day3.SVG"),
+            Some(ImageFormat::Svg)
+        ));
+    }
+}
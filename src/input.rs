@@ -0,0 +1,220 @@
+//! Reading and splitting puzzle inputs, shared by every day.
+//!
+//! Inputs are read whole and normalized once: CRLF line endings become LF and
+//! trailing blank lines are dropped, so a file saved on Windows or with an
+//! extra newline at the end gives the same lines as the original. Lines that
+//! are not UTF-8 are skipped, the rest of the input is still read.
+//!
+//! An input can be kept in memory with `keep`, the REPL does so for the input
+//! it works on, and reads of it skip the disk until it is released.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+static KEPT: RwLock<BTreeMap<PathBuf, String>> = RwLock::new(BTreeMap::new());
+
+/// The contents of `path` with CRLF line endings turned into LF.
+pub fn read_input<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let kept = KEPT.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(text) = kept.get(path.as_ref()) {
+        return Ok(text.clone());
+    }
+    drop(kept);
+    Ok(normalize(&read_text(path)?))
+}
+
+fn read_text<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(e) => {
+            let lines: Vec<&str> = e
+                .as_bytes()
+                .split(|&b| b == b'\n')
+                .filter_map(|line| std::str::from_utf8(line).ok())
+                .collect();
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// Reads `path` now and answers `read_input` of it from memory from then on.
+pub fn keep<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let text = normalize(&read_text(&path)?);
+    let mut kept = KEPT.write().unwrap_or_else(|e| e.into_inner());
+    kept.insert(path.as_ref().to_path_buf(), text);
+    Ok(())
+}
+
+/// Goes back to reading `path` from disk.
+pub fn release<P: AsRef<Path>>(path: P) {
+    let mut kept = KEPT.write().unwrap_or_else(|e| e.into_inner());
+    kept.remove(path.as_ref());
+}
+
+pub fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n")
+}
+
+/// The lines of `text` without line endings or trailing blank lines.
+pub fn lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text
+        .lines()
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .collect();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+pub fn read_lines<P: AsRef<Path>>(path: P) -> io::Result<Vec<String>> {
+    let text = read_input(path)?;
+    Ok(lines(&text).into_iter().map(str::to_string).collect())
+}
+
+/// Groups of lines separated by one or more blank lines.
+pub fn paragraphs(text: &str) -> Vec<Vec<&str>> {
+    let mut found = Vec::new();
+    let mut current = Vec::new();
+    for line in lines(text) {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                found.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        found.push(current);
+    }
+    found
+}
+
+/// One row of characters per line.
+pub fn grid(text: &str) -> Vec<Vec<char>> {
+    lines(text).iter().map(|l| l.chars().collect()).collect()
+}
+
+pub fn read_grid<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<char>>> {
+    Ok(grid(&read_input(path)?))
+}
+
+/// Every integer in `text`, with a '-' right before the digits making it
+/// negative. Numbers that do not fit in an `i64` are skipped.
+pub fn signed_ints(text: &str) -> Vec<i64> {
+    digit_runs(text)
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let negative = text[..start].ends_with('-');
+            let start = if negative { start - 1 } else { start };
+            text[start..end].parse().ok()
+        })
+        .collect()
+}
+
+/// Every run of digits in `text`, ignoring signs. Numbers that do not fit in
+/// a `u64` are skipped.
+pub fn unsigned_ints(text: &str) -> Vec<u64> {
+    digit_runs(text)
+        .into_iter()
+        .filter_map(|(start, end)| text[start..end].parse().ok())
+        .collect()
+}
+
+// Byte ranges of the runs of ASCII digits in `text`
+fn digit_runs(text: &str) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push((s, i));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(s) = start {
+        runs.push((s, text.len()));
+    }
+    runs
+}
+
+/// Splits `line` at the first `separator` and trims both sides.
+pub fn key_value<'a>(line: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let (key, value) = line.split_once(separator)?;
+    Some((key.trim(), value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_endings_do_not_matter() {
+        let unix = "a b\n\nc\n";
+        let windows = normalize("a b\r\n\r\nc\r\n\r\n");
+        assert_eq!(lines(unix), ["a b", "", "c"]);
+        assert_eq!(lines(&windows), lines(unix));
+        assert_eq!(lines("a\nb"), ["a", "b"]);
+        assert!(lines("\n\n").is_empty());
+    }
+
+    #[test]
+    fn splits_paragraphs_on_any_number_of_blank_lines() {
+        let text = "seeds: 1 2\n\na map:\n1 2 3\n \n\nb map:\n4 5 6\n";
+        assert_eq!(
+            paragraphs(text),
+            vec![
+                vec!["seeds: 1 2"],
+                vec!["a map:", "1 2 3"],
+                vec!["b map:", "4 5 6"]
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_integers() {
+        assert_eq!(signed_ints("x=-3, y=12 -z 4-5"), [-3, 12, 4, -5]);
+        assert_eq!(unsigned_ints("Time:  7 -15   30"), [7, 15, 30]);
+        assert_eq!(signed_ints("99999999999999999999 1"), [1]);
+        assert!(signed_ints("- no digits -").is_empty());
+    }
+
+    #[test]
+    fn splits_keys_from_values() {
+        assert_eq!(
+            key_value("AAA = (BBB, CCC)", "="),
+            Some(("AAA", "(BBB, CCC)"))
+        );
+        assert_eq!(key_value("Card 1: 41 48", ":"), Some(("Card 1", "41 48")));
+        assert_eq!(key_value("no separator", "="), None);
+        assert_eq!(grid("ab\ncd\n"), vec![vec!['a', 'b'], vec!['c', 'd']]);
+    }
+
+    #[test]
+    fn skips_lines_that_are_not_utf8() {
+        let path = std::env::temp_dir().join(format!("aoc-input-utf8-{}", std::process::id()));
+        fs::write(&path, b"a\n\xff\xfe\nb\n").unwrap();
+        assert_eq!(read_lines(&path).unwrap(), ["a", "b"]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn kept_inputs_are_read_from_memory() {
+        let path = std::env::temp_dir().join(format!("aoc-input-kept-{}", std::process::id()));
+        fs::write(&path, "a\r\nb\r\n").unwrap();
+        keep(&path).unwrap();
+        fs::write(&path, "changed\n").unwrap();
+        assert_eq!(read_lines(&path).unwrap(), ["a", "b"]);
+        release(&path);
+        assert_eq!(read_lines(&path).unwrap(), ["changed"]);
+        let _ = fs::remove_file(&path);
+    }
+}
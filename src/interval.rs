@@ -0,0 +1,297 @@
+//! Half-open intervals and sets of them.
+//!
+//! An `Interval` is `start..end`, end excluded, which makes lengths and
+//! adjacency exact: `0..5` and `5..9` touch without overlapping. A `RangeSet`
+//! keeps its intervals sorted, disjoint and coalesced, so puzzles that map
+//! huge ranges of numbers can work on a handful of intervals instead of
+//! every value.
+
+use num_traits::PrimInt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Copy + Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.is_empty() && !other.is_empty() && self.start < other.end && other.start < self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let both = Self::new(self.start.max(other.start), self.end.min(other.end));
+        (!both.is_empty()).then_some(both)
+    }
+
+    /// One interval covering both, when they overlap or touch.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if self.is_empty() {
+            return Some(*other);
+        }
+        if other.is_empty() {
+            return Some(*self);
+        }
+        if self.start > other.end || other.start > self.end {
+            return None;
+        }
+        Some(Self::new(
+            self.start.min(other.start),
+            self.end.max(other.end),
+        ))
+    }
+
+    /// The parts of `self` before, inside and after `other`, each `None` when empty.
+    pub fn split(&self, other: &Self) -> (Option<Self>, Option<Self>, Option<Self>) {
+        let non_empty = |i: Self| (!i.is_empty()).then_some(i);
+        let before = non_empty(Self::new(self.start, self.end.min(other.start)));
+        let after = non_empty(Self::new(self.start.max(other.end), self.end));
+        (before, self.intersection(other), after)
+    }
+
+    /// What is left of `self` without `other`, up to two intervals.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        let (before, _, after) = self.split(other);
+        before.into_iter().chain(after).collect()
+    }
+}
+
+impl<T: PrimInt> Interval<T> {
+    /// `len` values from `start`, `None` when the end would overflow.
+    pub fn from_len(start: T, len: T) -> Option<Self> {
+        Some(Self::new(start, start.checked_add(&len)?))
+    }
+
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    /// Moves the interval by `to - from`, which may be negative even for
+    /// unsigned `T`. `None` when either end would overflow.
+    pub fn shift(&self, from: T, to: T) -> Option<Self> {
+        let moved = |v: T| {
+            if to >= from {
+                v.checked_add(&(to - from))
+            } else {
+                v.checked_sub(&(from - to))
+            }
+        };
+        Some(Self::new(moved(self.start)?, moved(self.end)?))
+    }
+}
+
+/// Sorts `intervals` and merges the ones that overlap or touch, dropping empty ones.
+pub fn coalesce<T: Copy + Ord>(mut intervals: Vec<Interval<T>>) -> Vec<Interval<T>> {
+    intervals.retain(|i| !i.is_empty());
+    intervals.sort_by_key(|i| (i.start, i.end));
+    let mut merged: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged
+            .last_mut()
+            .and_then(|last| last.union(&interval).map(|u| (last, u)))
+        {
+            Some((last, union)) => *last = union,
+            None => merged.push(interval),
+        }
+    }
+    merged
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    /// Sorted, disjoint and never touching
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Copy + Ord> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy + Ord> RangeSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.intervals.iter().any(|i| i.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+        self.intervals = coalesce(intervals);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let both = self.intervals.iter().chain(&other.intervals).copied();
+        both.collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.intervals
+            .iter()
+            .flat_map(|a| other.intervals.iter().filter_map(|b| a.intersection(b)))
+            .collect()
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut remaining = self.intervals.clone();
+        for b in &other.intervals {
+            remaining = remaining.iter().flat_map(|a| a.difference(b)).collect();
+        }
+        remaining.into_iter().collect()
+    }
+}
+
+impl<T: PrimInt> RangeSet<T> {
+    /// How many values the set holds.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::zero(), |total, i| total + i.len())
+    }
+
+    /// Every interval moved by `to - from`, see `Interval::shift`.
+    pub fn shift(&self, from: T, to: T) -> Option<Self> {
+        self.intervals.iter().map(|i| i.shift(from, to)).collect()
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Interval<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self {
+            intervals: coalesce(iter.into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i32, i32)]) -> RangeSet<i32> {
+        intervals
+            .iter()
+            .map(|&(s, e)| Interval::new(s, e))
+            .collect()
+    }
+
+    // Every value of -10..30 in the set, the slow way
+    fn values(set: &RangeSet<i32>) -> Vec<i32> {
+        (-10..30).filter(|&v| set.contains(v)).collect()
+    }
+
+    #[test]
+    fn intervals_are_half_open() {
+        let a = Interval::new(0, 5);
+        assert!(a.contains(0) && !a.contains(5));
+        assert!(!a.overlaps(&Interval::new(5, 9)));
+        assert_eq!(a.union(&Interval::new(5, 9)), Some(Interval::new(0, 9)));
+        assert_eq!(a.union(&Interval::new(6, 9)), None);
+        assert_eq!(
+            a.intersection(&Interval::new(4, 9)),
+            Some(Interval::new(4, 5))
+        );
+        assert_eq!(a.len(), 5);
+        assert_eq!(Interval::new(3, 3).len(), 0);
+        assert_eq!(Interval::<u8>::from_len(250, 10), None);
+    }
+
+    #[test]
+    fn splits_by_another_interval() {
+        let a = Interval::new(0, 10);
+        assert_eq!(
+            a.split(&Interval::new(3, 6)),
+            (
+                Some(Interval::new(0, 3)),
+                Some(Interval::new(3, 6)),
+                Some(Interval::new(6, 10))
+            )
+        );
+        assert_eq!(
+            a.split(&Interval::new(-5, 3)),
+            (None, Some(Interval::new(0, 3)), Some(Interval::new(3, 10)))
+        );
+        assert_eq!(a.split(&Interval::new(20, 30)), (Some(a), None, None));
+        assert_eq!(a.difference(&Interval::new(-1, 11)), vec![]);
+    }
+
+    #[test]
+    fn shifts_unsigned_intervals_both_ways() {
+        let a = Interval::new(98u64, 100);
+        assert_eq!(a.shift(98, 50), Some(Interval::new(50, 52)));
+        assert_eq!(a.shift(50, 98), Some(Interval::new(146, 148)));
+        assert_eq!(a.shift(99, 0), None);
+        let s: RangeSet<u64> = [Interval::new(1, 3), Interval::new(10, 12)]
+            .into_iter()
+            .collect();
+        assert_eq!(s.shift(0, 5).unwrap().min(), Some(6));
+        assert_eq!(s.len(), 4);
+    }
+
+    #[test]
+    fn coalesces_overlapping_and_touching_intervals() {
+        let s = set(&[(5, 8), (0, 2), (2, 4), (7, 10), (12, 12)]);
+        assert_eq!(s.intervals(), &[Interval::new(0, 4), Interval::new(5, 10)]);
+        let mut s = s;
+        s.insert(Interval::new(4, 5));
+        assert_eq!(s.intervals(), &[Interval::new(0, 10)]);
+    }
+
+    #[test]
+    fn set_operations_match_brute_force() {
+        let sets = [
+            set(&[]),
+            set(&[(0, 5)]),
+            set(&[(-3, 2), (4, 9), (15, 20)]),
+            set(&[(1, 2), (3, 4), (5, 6), (18, 25)]),
+            set(&[(-10, 30)]),
+        ];
+        for a in &sets {
+            for b in &sets {
+                let (va, vb) = (values(a), values(b));
+                let union: Vec<i32> = (-10..30)
+                    .filter(|v| va.contains(v) || vb.contains(v))
+                    .collect();
+                let both: Vec<i32> = va.iter().copied().filter(|v| vb.contains(v)).collect();
+                let only_a: Vec<i32> = va.iter().copied().filter(|v| !vb.contains(v)).collect();
+                assert_eq!(values(&a.union(b)), union);
+                assert_eq!(values(&a.intersection(b)), both);
+                assert_eq!(values(&a.difference(b)), only_a);
+                assert_eq!(a.union(b).len() as usize, union.len());
+            }
+        }
+    }
+}
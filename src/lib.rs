@@ -0,0 +1,103 @@
+pub mod aoc;
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod dashboard;
+pub mod differential;
+pub mod explain;
+pub mod fuzz;
+pub mod generate;
+pub mod graph;
+pub mod grid;
+pub mod history;
+pub mod image;
+pub mod input;
+pub mod interval;
+pub mod math;
+pub mod numeric;
+pub mod parallel;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod plugin;
+pub mod progress;
+pub mod repl;
+pub mod runner;
+pub mod scan;
+pub mod serve;
+pub mod snapshot;
+pub mod variants;
+
+pub use aoc::{Aoc, AocError, AocRes};
+pub use day1::Day1;
+pub use day2::Day2;
+pub use day3::Day3;
+pub use day4::Day4;
+pub use day5::Day5;
+pub use day6::Day6;
+pub use day7::Day7;
+pub use day8::Day8;
+pub use day9::Day9;
+pub use day10::Day10;
+
+/// Number of days that have a solver in this crate.
+pub const NUM_DAYS: u32 = 10;
+
+/// Puzzle title of `day`, as given at the top of its task description.
+pub fn title_for_day(day: u32) -> Option<&'static str> {
+    let title = match day {
+        1 => "Trebuchet?!",
+        2 => "Cube Conundrum",
+        3 => "Gear Ratios",
+        4 => "Scratchcards",
+        5 => "If You Give A Seed A Fertilizer",
+        6 => "Wait For It",
+        7 => "Camel Cards",
+        8 => "Haunted Wasteland",
+        9 => "Mirage Maintenance",
+        10 => "Pipe Maze",
+        _ => return None,
+    };
+    Some(title)
+}
+
+/// Where the puzzle input of `day` lives, in the configured inputs directory.
+pub fn input_path_for_day(day: u32) -> String {
+    config::current().input_path(day).display().to_string()
+}
+
+/// Where the example from the task description of `day` lives, for days where we saved it.
+pub fn example_input_path_for_day(day: u32) -> String {
+    config::current().example_input_path(day).display().to_string()
+}
+
+/// Where the puzzle text of `day` lives, for days where we saved it.
+pub fn task_path_for_day(day: u32) -> String {
+    config::current().task_path(day).display().to_string()
+}
+
+/// Builds the solver for `day`, reading its puzzle input from `path_to_input`.
+/// Returns `None` for days that have not been solved yet.
+pub fn solver_for_day(day: u32, path_to_input: &str) -> Option<Box<dyn Aoc>> {
+    let solver: Box<dyn Aoc> = match day {
+        1 => Box::new(Day1::new(path_to_input)),
+        2 => Box::new(Day2::new(path_to_input)),
+        3 => Box::new(Day3::new(path_to_input)),
+        4 => Box::new(Day4::new(path_to_input)),
+        5 => Box::new(Day5::new(path_to_input)),
+        6 => Box::new(Day6::new(path_to_input)),
+        7 => Box::new(Day7::new(path_to_input)),
+        8 => Box::new(Day8::new(path_to_input)),
+        9 => Box::new(Day9::new(path_to_input)),
+        10 => Box::new(Day10::new(path_to_input)),
+        _ => return None,
+    };
+    Some(solver)
+}
@@ -1,46 +1,607 @@
-mod aoc;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-
-use aoc::Aoc;
-use day1::Day1;
-use day2::Day2;
-use day3::Day3;
-use day4::Day4;
-use day5::Day5;
-use day6::Day6;
-use day7::Day7;
-use day8::Day8;
-use day9::Day9;
-use day10::Day10;
+use aoc::cache::ResultCache;
+use aoc::check::check_file;
+use aoc::config::{self, Config, OutputFormat};
+use aoc::dashboard::Dashboard;
+use aoc::differential::{find_disagreement, references, save_regression, DEFAULT_REGRESSION_DIR};
+use aoc::fuzz::{fuzz, save_crash, Failure, DEFAULT_CRASH_DIR};
+use aoc::generate::generate_input;
+use aoc::history;
+use aoc::image::ImageFormat;
+use aoc::numeric;
+use aoc::plugin::{load_plugins, Plugin};
+use aoc::repl::Repl;
+use aoc::explain::json_string;
+use aoc::progress;
+use aoc::runner::{run_part, run_part_within};
+use aoc::serve::{Server, DEFAULT_ADDR};
+use aoc::snapshot::{self, Status, DEFAULT_SNAPSHOT_DIR};
+use aoc::variants::{agree, report, run_all, run_variant, variants_of};
+use aoc::{input_path_for_day, solver_for_day, Aoc, NUM_DAYS};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::panic;
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "usage: aoc [OPTION...] [--no-cache] [--checked] [--plugins DIR] [--explain[=table|json]] [DAY...]
+       aoc repl
+       aoc dashboard
+       aoc serve [ADDR]
+       aoc check DAY [PATH]
+       aoc generate DAY [SIZE] [SEED]
+       aoc diff [MAX_SIZE] [SEEDS]
+       aoc fuzz DAY [ITERATIONS] [SEED]
+       aoc image DAY OUT.{ppm,png,svg} [PATH]
+       aoc variants DAY [PART [NAME]]
+       aoc history [DAY [PART]]
+       aoc snapshots [review|accept]
+options, overriding aoc.toml and AOC_* variables:
+       --config PATH  --inputs DIR  --tasks DIR  --year YEAR  --threads N
+       --timeout SECONDS  --output text|json  -v, --verbose  -q, --quiet";
+
+#[derive(Clone, Copy)]
+enum ExplainFormat {
+    Table,
+    Json,
+}
 
 fn main() {
+    load_config();
+    // Runs the configured days, or every day, when no day is given.
+    let mut days = Vec::<u32>::new();
+    let mut plugin_dir: Option<String> = None;
+    let mut use_cache = true;
+    let mut explain: Option<ExplainFormat> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "repl" => {
+                let mut repl = Repl::default();
+                if let Err(e) = repl.run(io::stdin().lock(), io::stdout()) {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+                return;
+            }
+            "dashboard" => {
+                if let Err(e) = Dashboard::new().run() {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+                return;
+            }
+            "serve" => {
+                let addr = args.next().unwrap_or_else(|| DEFAULT_ADDR.to_string());
+                let cache = use_cache.then(|| ResultCache::new(&config::current().cache));
+                progress::suppress_bar(true);
+                let server = Server::bind(&addr, cache).and_then(|server| {
+                    eprintln!("listening on http://{}", server.local_addr()?);
+                    server.run()
+                });
+                if let Err(e) = server {
+                    eprintln!("{}: {}", addr, e);
+                    process::exit(1);
+                }
+                return;
+            }
+            "check" => {
+                let day = args.next().and_then(|d| d.parse::<u32>().ok());
+                let Some(day) = day.filter(|d| (1..=NUM_DAYS).contains(d)) else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                let path_to_input = args.next().unwrap_or_else(|| input_path_for_day(day));
+                process::exit(check(day, &path_to_input));
+            }
+            "generate" => {
+                let day = args.next().and_then(|d| d.parse::<u32>().ok());
+                let size = args.next().map_or(Ok(10), |s| s.parse::<usize>());
+                let seed = args.next().map_or(Ok(0), |s| s.parse::<u64>());
+                let input = match (day, size, seed) {
+                    (Some(day), Ok(size), Ok(seed)) => generate_input(day, size, seed),
+                    _ => None,
+                };
+                let Some(input) = input else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                println!("{}", input);
+                return;
+            }
+            "diff" => {
+                let max_size = args.next().map_or(Ok(20), |s| s.parse::<usize>());
+                let num_seeds = args.next().map_or(Ok(20), |s| s.parse::<u64>());
+                let (Ok(max_size), Ok(num_seeds)) = (max_size, num_seeds) else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                process::exit(differential(max_size, num_seeds));
+            }
+            "fuzz" => {
+                let day = args.next().and_then(|d| d.parse::<u32>().ok());
+                let iterations = args.next().map_or(Ok(1000), |s| s.parse::<usize>());
+                let seed = args.next().map_or(Ok(0), |s| s.parse::<u64>());
+                let (Some(day), Ok(iterations), Ok(seed)) = (day, iterations, seed) else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                if !(1..=NUM_DAYS).contains(&day) {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                }
+                // Overflows are errors of their own, not crashes
+                numeric::set_checked(true);
+                process::exit(fuzz_day(day, iterations, seed));
+            }
+            "image" => {
+                let day = args.next().and_then(|d| d.parse::<u32>().ok());
+                let out = args.next();
+                let format = out.as_ref().and_then(ImageFormat::from_path);
+                let (Some(day), Some(out), Some(format)) = (day, out, format) else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                let path_to_input = args.next().unwrap_or_else(|| input_path_for_day(day));
+                let image = solver_for_day(day, &path_to_input).and_then(|s| s.image());
+                let Some(image) = image else {
+                    eprintln!("day {} has no image", day);
+                    process::exit(1);
+                };
+                if let Err(e) = fs::write(&out, image.encode(format)) {
+                    eprintln!("{}: {}", out, e);
+                    process::exit(1);
+                }
+                return;
+            }
+            "variants" => {
+                let day = args.next().and_then(|d| d.parse::<u32>().ok());
+                let part = args.next().map(|p| p.parse::<u32>());
+                let name = args.next();
+                let day = day.filter(|d| (1..=NUM_DAYS).contains(d));
+                let parts = match part {
+                    None => vec![1, 2],
+                    Some(Ok(part)) if part == 1 || part == 2 => vec![part],
+                    _ => Vec::new(),
+                };
+                let (Some(day), false) = (day, parts.is_empty()) else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                process::exit(variants(day, &parts, name.as_deref()));
+            }
+            "--config" => {
+                // Already applied by `load_config`
+                args.next();
+            }
+            "--inputs" | "--tasks" | "--year" | "--threads" | "--timeout" | "--output" => {
+                set_option(&arg[2..], args.next());
+            }
+            "-v" | "--verbose" => set_option("verbosity", Some("2".to_string())),
+            "-q" | "--quiet" => set_option("verbosity", Some("0".to_string())),
+            "history" => {
+                let day = args.next().map(|d| d.parse::<u32>());
+                let part = args.next().map(|p| p.parse::<u32>());
+                let (day, part) = match (day, part) {
+                    (None, _) => (None, None),
+                    (Some(Ok(day)), None) => (Some(day), None),
+                    (Some(Ok(day)), Some(Ok(part))) => (Some(day), Some(part)),
+                    _ => {
+                        eprintln!("{}", USAGE);
+                        process::exit(2);
+                    }
+                };
+                process::exit(show_history(day, part));
+            }
+            "snapshots" => {
+                let dir = Path::new(DEFAULT_SNAPSHOT_DIR);
+                let status = match args.next().as_deref() {
+                    None => check_snapshots(dir),
+                    Some("review") => review_snapshots(dir, false),
+                    Some("accept") => review_snapshots(dir, true),
+                    Some(_) => {
+                        eprintln!("{}", USAGE);
+                        2
+                    }
+                };
+                process::exit(status);
+            }
+            "--no-cache" => use_cache = false,
+            "--checked" => numeric::set_checked(true),
+            "--explain" | "--explain=table" => explain = Some(ExplainFormat::Table),
+            "--explain=json" => explain = Some(ExplainFormat::Json),
+            "--plugins" => {
+                let Some(dir) = args.next() else {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                };
+                plugin_dir = Some(dir);
+            }
+            _ => match arg.parse::<u32>() {
+                Ok(day) if (1..=NUM_DAYS).contains(&day) => days.push(day),
+                _ => {
+                    eprintln!("unknown day: {}\n{}", arg, USAGE);
+                    process::exit(2);
+                }
+            },
+        }
+    }
+    let config = config::current();
+    if days.is_empty() {
+        days = config.days.clone();
+    }
+    if days.is_empty() {
+        days = (1..=NUM_DAYS).collect();
+    }
+    progress::suppress_bar(config.verbosity == 0);
+
+    let mut plugins = Vec::<Plugin>::new();
+    if let Some(dir) = plugin_dir {
+        match load_plugins(&dir) {
+            Ok(loaded) => {
+                for plugin in loaded {
+                    match plugin {
+                        Ok(plugin) => plugins.push(plugin),
+                        Err(e) => eprintln!("skipping {}", e),
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    let cache = use_cache.then(|| ResultCache::new(&config.cache));
+
+    let mut status = 0;
+    for day in days {
+        let path_to_input = input_path_for_day(day);
+        let Some(solver) = solver_for_day(day, &path_to_input) else {
+            continue;
+        };
+        if let Some(format) = explain {
+            explain_day(solver.as_ref(), day, format);
+            continue;
+        }
+        for part in 1..=2 {
+            let run = match config.timeout {
+                Some(timeout) => run_part_within(day, part, &path_to_input, cache.as_ref(), timeout),
+                None => run_part(solver.as_ref(), day, part, &path_to_input, cache.as_ref()),
+            };
+            let run = match run {
+                Ok(run) => run,
+                Err(e) if config.output == OutputFormat::Json => {
+                    let error = json_string(&e.to_string());
+                    println!("{{\"day\": {}, \"part\": {}, \"error\": {}}}", day, part, error);
+                    status = 1;
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    status = 1;
+                    continue;
+                }
+            };
+            let answer = run.answer;
+            match config.output {
+                OutputFormat::Json => println!("{}", run.to_json()),
+                OutputFormat::Text if config.verbosity < 2 && run.cached => {
+                    println!("day{} - part{}: {} (cached)", day, part, answer);
+                }
+                OutputFormat::Text if config.verbosity < 2 => {
+                    println!("day{} - part{}: {}", day, part, answer);
+                }
+                OutputFormat::Text => {
+                    let cached = if run.cached { ", cached" } else { "" };
+                    println!("day{} - part{}: {} ({:.3?}{})", day, part, answer, run.duration, cached);
+                }
+            }
+            for plugin in plugins.iter().filter(|p| p.day == day) {
+                let res = plugin.solve(part, &path_to_input);
+                match (config.output, res) {
+                    (OutputFormat::Json, Ok(res)) => println!(
+                        "{{\"day\": {}, \"part\": {}, \"plugin\": {}, \"answer\": {}, \"agrees\": {}}}",
+                        day,
+                        part,
+                        json_string(&plugin.title),
+                        res,
+                        res == answer
+                    ),
+                    (OutputFormat::Json, Err(e)) => println!(
+                        "{{\"day\": {}, \"part\": {}, \"plugin\": {}, \"error\": {}}}",
+                        day,
+                        part,
+                        json_string(&plugin.title),
+                        json_string(&e.to_string())
+                    ),
+                    (OutputFormat::Text, Ok(res)) => {
+                        let verdict = if res == answer { "agrees" } else { "DIFFERS" };
+                        println!("  plugin \"{}\": {} ({})", plugin.title, res, verdict);
+                    }
+                    (OutputFormat::Text, Err(e)) => {
+                        println!("  plugin \"{}\": error: {}", plugin.title, e)
+                    }
+                }
+            }
+        }
+    }
+    process::exit(status);
+}
+
+// Loads aoc.toml, or the file given with --config, and the environment, so
+// that command line options can be applied on top as they are parsed
+fn load_config() {
+    let args: Vec<String> = env::args().collect();
+    let file = args
+        .windows(2)
+        .find(|pair| pair[0] == "--config")
+        .map(|pair| Path::new(&pair[1]));
+    match Config::load(file) {
+        Ok(loaded) => {
+            history::set_log(Some(loaded.history.clone()));
+            config::set(loaded);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    }
+}
+
+// Applies one command line option, exiting with the usage when it is invalid
+fn set_option(key: &str, value: Option<String>) {
+    let Some(value) = value else {
+        eprintln!("--{} needs a value\n{}", key, USAGE);
+        process::exit(2);
+    };
+    if let Err(e) = config::update(|config| config.apply(key, &value)) {
+        eprintln!("--{} {}: {}\n{}", key, value, e, USAGE);
+        process::exit(2);
+    }
+}
+
+// Prints the trends in the history log and returns the exit status, 1 when an
+// answer changed for the same input
+fn show_history(day: Option<u32>, part: Option<u32>) -> i32 {
+    let path = config::current().history.clone();
+    let entries = match history::read(&path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            return 2;
+        }
+    };
+    let (report, changed) = history::report(&entries, day, part);
+    if report.is_empty() {
+        println!("no runs logged in {}", path.display());
+    }
+    print!("{}", report);
+    i32::from(changed)
+}
+
+// Compares every snapshot with the stored one and returns the exit status, 1
+// when some are waiting for review
+fn check_snapshots(dir: &Path) -> i32 {
+    let outcomes = match snapshot::check(dir) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            eprintln!("{}: {}", dir.display(), e);
+            return 2;
+        }
+    };
+    let mut status = 0;
+    for outcome in &outcomes {
+        match &outcome.status {
+            Status::Unchanged => println!("{}: ok", outcome.name),
+            Status::New => println!("{}: new", outcome.name),
+            Status::Changed { expected } => {
+                println!("{}: CHANGED", outcome.name);
+                print!("{}", snapshot::diff(expected, &outcome.actual));
+            }
+        }
+        if outcome.status != Status::Unchanged {
+            status = 1;
+        }
+    }
+    if status != 0 {
+        println!("run `aoc snapshots review` to accept or reject the changes");
+    }
+    status
+}
+
+// Asks about every snapshot waiting for review, or accepts all of them, and
+// returns the exit status
+fn review_snapshots(dir: &Path, accept_all: bool) -> i32 {
+    let names = match snapshot::pending(dir) {
+        Ok(names) => names,
+        Err(e) => {
+            eprintln!("{}: {}", dir.display(), e);
+            return 2;
+        }
+    };
+    if names.is_empty() {
+        println!("no snapshots to review, run `aoc snapshots` first");
+    }
+    let mut answers = io::stdin().lock().lines();
+    for name in names {
+        let (stored, actual) = match snapshot::review(dir, &name) {
+            Ok(review) => review,
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                return 2;
+            }
+        };
+        let accepted = if accept_all {
+            true
+        } else {
+            match &stored {
+                Some(stored) => println!("{}:\n{}", name, snapshot::diff(stored, &actual)),
+                None => println!("{} (new):\n{}", name, snapshot::diff("", &actual)),
+            }
+            print!("accept {}? [y/N/q] ", name);
+            let _ = io::stdout().flush();
+            match answers.next().and_then(Result::ok).as_deref().map(str::trim) {
+                Some("y") | Some("Y") => true,
+                Some("q") | Some("Q") | None => return 0,
+                Some(_) => false,
+            }
+        };
+        let done = if accepted {
+            snapshot::accept(dir, &name)
+        } else {
+            snapshot::reject(dir, &name)
+        };
+        match done {
+            Ok(()) if accepted => println!("{}: accepted", name),
+            Ok(()) => println!("{}: rejected", name),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                return 2;
+            }
+        }
+    }
+    0
+}
+
+// Prints every problem in the input and returns the exit status
+fn check(day: u32, path_to_input: &str) -> i32 {
+    match check_file(day, path_to_input) {
+        Ok(diagnostics) if diagnostics.is_empty() => {
+            println!("{}: ok", path_to_input);
+            0
+        }
+        Ok(diagnostics) => {
+            for diagnostic in &diagnostics {
+                println!("{}\n", diagnostic);
+            }
+            println!("{}: {} problem(s)", path_to_input, diagnostics.len());
+            1
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path_to_input, e);
+            2
+        }
+    }
+}
+
+// Compares every optimized part with its reference, saving the smallest
+// disagreement of each, and returns the exit status
+fn differential(max_size: usize, num_seeds: u64) -> i32 {
+    let mut status = 0;
+    for reference in references() {
+        let found = find_disagreement(&reference, max_size, num_seeds);
+        print!("day{} - part{} vs {}: ", reference.day, reference.part, reference.name);
+        let found = match found {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                println!("agree");
+                continue;
+            }
+            Err(e) => {
+                println!("could not run: {}", e);
+                status = 1;
+                continue;
+            }
+        };
+        status = 1;
+        match &found.actual {
+            Ok(actual) => println!("expected {}, got {}", found.expected, actual),
+            Err(e) => println!("expected {}, {}", found.expected, e),
+        }
+        println!("{}", found.input);
+        match save_regression(DEFAULT_REGRESSION_DIR, &found) {
+            Ok(path) => println!("saved to {}", path.display()),
+            Err(e) => eprintln!("failed to save regression: {}", e),
+        }
+    }
+    status
+}
+
+// Fuzzes one day, saving the shrunk input of a panic, and returns the exit status
+fn fuzz_day(day: u32, iterations: usize, seed: u64) -> i32 {
+    // Crashes are reported below, once they have been shrunk
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let crash = fuzz(day, iterations, seed);
+    panic::set_hook(default_hook);
+    let Some(crash) = crash else {
+        println!("day{}: no crash in {} inputs", day, iterations);
+        return 0;
+    };
+    println!("{}", String::from_utf8_lossy(&crash.input));
+    let Failure::Panic(msg) = &crash.failure else {
+        println!("day{}: hung for more than {:?}", day, aoc::fuzz::TIMEOUT);
+        return 1;
+    };
+    println!("day{}: panicked: {}", day, msg);
+    match save_crash(DEFAULT_CRASH_DIR, &crash) {
+        Ok(path) => println!("saved to {}", path.display()),
+        Err(e) => eprintln!("failed to save crash: {}", e),
+    }
+    1
+}
+
+// Runs the variant called `name`, or compares all of them, and returns the exit status
+fn variants(day: u32, parts: &[u32], name: Option<&str>) -> i32 {
+    let path_to_input = input_path_for_day(day);
+    let Some(solver) = solver_for_day(day, &path_to_input) else {
+        return 1;
+    };
+    let mut status = 0;
+    for &part in parts {
+        if let Some(name) = name {
+            let Some(run) = run_variant(solver.as_ref(), day, part, name) else {
+                let names: Vec<&str> = variants_of(solver.as_ref(), part)
+                    .iter()
+                    .map(|v| v.name)
+                    .collect();
+                eprintln!(
+                    "day{} - part{} has no variant \"{}\", try one of: {}",
+                    day,
+                    part,
+                    name,
+                    names.join(", ")
+                );
+                return 2;
+            };
+            if let Some(answer) = run.answer {
+                println!("day{} - part{} {}: {} ({:.3?})", day, part, name, answer, run.duration);
+            }
+            continue;
+        }
+        let runs = run_all(solver.as_ref(), day, part, false);
+        let verdict = if agree(&runs) { "agree" } else { "DISAGREE" };
+        println!("day{} - part{}: {} variant(s) {}", day, part, runs.len(), verdict);
+        print!("{}", report(&runs));
+        if !agree(&runs) {
+            status = 1;
+        }
+    }
+    status
+}
 
-    let mut aoc = Vec::<Box<dyn Aoc>>::new();
-
-    aoc.push(Box::new(Day1::new(&"./inputs/day1.input".to_string())));
-    aoc.push(Box::new(Day2::new(&"./inputs/day2.input".to_string())));
-    aoc.push(Box::new(Day3::new(&"./inputs/day3.input".to_string())));
-    aoc.push(Box::new(Day4::new(&"./inputs/day4.input".to_string())));
-    aoc.push(Box::new(Day5::new(&"./inputs/day5.input".to_string())));
-    aoc.push(Box::new(Day6::new(&"./inputs/day6.input".to_string())));
-    aoc.push(Box::new(Day7::new(&"./inputs/day7.input".to_string())));
-    aoc.push(Box::new(Day8::new(&"./inputs/day8.input".to_string())));
-    aoc.push(Box::new(Day9::new(&"./inputs/day9.input".to_string())));
-    aoc.push(Box::new(Day10::new(&"./inputs/day10.input".to_string())));
-
-
-    aoc.into_iter().fold(1, |acc, el| {
-        println!("day{} - part1: {}", acc, el.part1());
-        println!("day{} - part2: {}", acc, el.part2());
-        acc + 1
-    });
+// Prints the breakdown of both parts, one JSON object per line in JSON format
+fn explain_day(solver: &dyn Aoc, day: u32, format: ExplainFormat) {
+    for part in 1..=2 {
+        let explanation = solver.explain(part);
+        match (format, explanation) {
+            (ExplainFormat::Table, Some(explanation)) => {
+                println!("day{} - part{}:\n{}\n", day, part, explanation.to_table());
+            }
+            (ExplainFormat::Json, Some(explanation)) => println!(
+                "{{\"day\": {}, \"part\": {}, \"explanation\": {}}}",
+                day,
+                part,
+                explanation.to_json()
+            ),
+            (ExplainFormat::Table, None) => {
+                println!("day{} - part{}: no explanation\n", day, part);
+            }
+            (ExplainFormat::Json, None) => {
+                println!("{{\"day\": {}, \"part\": {}, \"explanation\": null}}", day, part);
+            }
+        }
+    }
 }
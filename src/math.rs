@@ -0,0 +1,202 @@
+//! Number theory shared by the days: gcd and lcm, extended Euclid, modular
+//! inverses and powers, and the Chinese Remainder Theorem.
+//!
+//! Anything that can overflow returns `None` instead of wrapping, and zero is
+//! a valid input everywhere except as a modulus.
+
+/// Greatest common divisor, with `gcd(0, 0) == 0`.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, `None` when it does not fit in a `u64`.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn gcd_all(values: &[u64]) -> u64 {
+    values.iter().fold(0, |acc, &v| gcd(acc, v))
+}
+
+/// Least common multiple of all of `values`, 1 for none.
+pub fn lcm_all(values: &[u64]) -> Option<u64> {
+    values.iter().try_fold(1, |acc, &v| lcm(acc, v))
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y == g` and `g` the non-negative gcd,
+/// `None` when one of them does not fit in an `i64`.
+pub fn extended_gcd(a: i64, b: i64) -> Option<(i64, i64, i64)> {
+    let (g, x, y) = extended_gcd_wide(a as i128, b as i128);
+    Some((i64::try_from(g).ok()?, i64::try_from(x).ok()?, i64::try_from(y).ok()?))
+}
+
+fn extended_gcd_wide(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The `x` in `0..m` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd_wide(a as i128, m as i128);
+    (g == 1).then(|| x.rem_euclid(m as i128) as i64)
+}
+
+/// `base` to the power `exp`, modulo `modulus`, which must not be zero.
+pub fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let m = modulus as u128;
+    let mut result = 1 % m;
+    let mut base = base as u128 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// Solves `x ≡ r (mod m)` for every `(r, m)` in `congruences` at once, the
+/// moduli need not be coprime. Returns `(x, lcm of the moduli)` with `x` the
+/// smallest non-negative solution, or `None` when the congruences contradict
+/// each other, a modulus is not positive or the lcm overflows an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let (mut x, mut m): (i128, i128) = (0, 1);
+    for &(r, n) in congruences {
+        if n <= 0 {
+            return None;
+        }
+        let n = n as i128;
+        let r = (r as i128).rem_euclid(n);
+        // x + m * k ≡ r (mod n) has a solution only when gcd(m, n) divides r - x
+        let (g, p, _) = extended_gcd_wide(m, n);
+        if (r - x) % g != 0 {
+            return None;
+        }
+        let step = n / g;
+        let k = ((r - x) / g % step * p).rem_euclid(step);
+        x += m * k;
+        m *= step;
+        if m > i64::MAX as i128 {
+            return None;
+        }
+        x = x.rem_euclid(m);
+    }
+    Some((x as i64, m as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_gcd(a: u64, b: u64) -> u64 {
+        (1..=a.max(b))
+            .rev()
+            .find(|&d| a.is_multiple_of(d) && b.is_multiple_of(d))
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn gcd_and_lcm_match_brute_force() {
+        for a in 0..40 {
+            for b in 0..40 {
+                assert_eq!(gcd(a, b), brute_gcd(a, b), "gcd({}, {})", a, b);
+                let expected = if a == 0 || b == 0 {
+                    0
+                } else {
+                    (1..=a * b).find(|n| n % a == 0 && n % b == 0).unwrap()
+                };
+                assert_eq!(lcm(a, b), Some(expected), "lcm({}, {})", a, b);
+            }
+        }
+        assert_eq!(gcd_all(&[12, 18, 0, 30]), 6);
+        assert_eq!(lcm_all(&[3, 3, 2]), Some(6));
+        assert_eq!(lcm_all(&[]), Some(1));
+    }
+
+    #[test]
+    fn lcm_reports_overflow() {
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(lcm(1 << 40, 1 << 40), Some(1 << 40));
+        assert_eq!(lcm_all(&[3, u64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn extended_gcd_gives_bezout_coefficients() {
+        for a in -30..30 {
+            for b in -30..30 {
+                let (g, x, y) = extended_gcd(a, b).unwrap();
+                assert_eq!(g as u64, brute_gcd(a.unsigned_abs(), b.unsigned_abs()));
+                assert_eq!(a * x + b * y, g, "extended_gcd({}, {})", a, b);
+            }
+        }
+        // The gcd of i64::MIN and 0 is 2^63, one more than an i64 holds
+        assert_eq!(extended_gcd(i64::MIN, 0), None);
+        assert_eq!(extended_gcd(i64::MIN, 1), Some((1, 0, 1)));
+    }
+
+    #[test]
+    fn inverses_and_powers_match_brute_force() {
+        for m in 1..30i64 {
+            for a in -30..30 {
+                let expected = (0..m).find(|x| (a * x).rem_euclid(m) == 1 % m);
+                assert_eq!(mod_inverse(a, m), expected, "mod_inverse({}, {})", a, m);
+            }
+            for base in 0..10 {
+                for exp in 0..10 {
+                    let expected = (0..exp).fold(1 % m as u64, |acc, _| acc * base % m as u64);
+                    assert_eq!(mod_pow(base, exp, m as u64), expected);
+                }
+            }
+        }
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_pow(u64::MAX, u64::MAX, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn crt_matches_brute_force() {
+        for m1 in 1..13 {
+            for m2 in 1..13 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let congruences = [(r1, m1), (r2, m2)];
+                        let modulus = lcm(m1 as u64, m2 as u64).unwrap() as i64;
+                        let expected = (0..modulus).find(|x| x % m1 == r1 && x % m2 == r2);
+                        assert_eq!(
+                            crt(&congruences),
+                            expected.map(|x| (x, modulus)),
+                            "{:?}",
+                            congruences
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(-1, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(0, i64::MAX), (0, i64::MAX - 1)]), None);
+    }
+}
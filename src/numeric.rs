@@ -0,0 +1,226 @@
+//! Overflow-checked arithmetic for answers.
+//!
+//! Solvers do the arithmetic that builds an answer on `Num<T>`. Normally its
+//! operators are the plain integer ones, which wrap silently in release
+//! builds. With checking on, through the `checked` feature or `aoc --checked`,
+//! an overflow unwinds with an `Overflow` naming the operation instead, and
+//! `runner::try_solve_part` turns it into an `AocError` for the day and part.
+
+use num_traits::{AsPrimitive, PrimInt};
+use std::any::type_name;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once};
+
+static CHECKED: AtomicBool = AtomicBool::new(cfg!(feature = "checked"));
+static QUIET_HOOK: Once = Once::new();
+static SCOPE: Mutex<()> = Mutex::new(());
+
+pub fn set_checked(on: bool) {
+    CHECKED.store(on, Ordering::Relaxed);
+}
+
+pub fn is_checked() -> bool {
+    CHECKED.load(Ordering::Relaxed)
+}
+
+/// Turns checking on or off until the returned guard is dropped, which puts
+/// back what it was before. Scopes on other threads wait for this one to end,
+/// so they can't restore each other's setting; a thread must not nest them.
+#[must_use]
+pub fn checked_scope(on: bool) -> CheckedScope {
+    let lock = SCOPE.lock().unwrap_or_else(|e| e.into_inner());
+    CheckedScope {
+        previous: CHECKED.swap(on, Ordering::Relaxed),
+        _lock: lock,
+    }
+}
+
+pub struct CheckedScope {
+    previous: bool,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for CheckedScope {
+    fn drop(&mut self) {
+        set_checked(self.previous);
+    }
+}
+
+/// The operation that overflowed, like `i32: 2147483647 + 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub operation: String,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} overflows", self.operation)
+    }
+}
+
+#[cold]
+fn overflow(operation: String) -> ! {
+    // An overflow is reported as an error, the panic message would only repeat it
+    QUIET_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !info.payload().is::<Overflow>() {
+                default_hook(info);
+            }
+        }));
+    });
+    panic::panic_any(Overflow { operation })
+}
+
+/// Runs `f`, returning the overflow it unwound with, if any. Other panics are
+/// passed on.
+pub fn catch_overflow<R>(f: impl FnOnce() -> R) -> Result<R, Overflow> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => Ok(value),
+        Err(payload) => match payload.downcast::<Overflow>() {
+            Ok(overflow) => Err(*overflow),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+// "i32" rather than a path, there are only primitives here
+fn short_type_name<T>() -> &'static str {
+    type_name::<T>().rsplit("::").next().unwrap_or("integer")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Num<T>(pub T);
+
+impl<T: PrimInt + fmt::Display> Num<T> {
+    pub fn get(self) -> T {
+        self.0
+    }
+
+    /// `value` as a `T`, truncated like `as` unless checking is on.
+    pub fn from_int<U>(value: U) -> Self
+    where
+        U: PrimInt + AsPrimitive<T> + fmt::Display,
+        T: 'static,
+    {
+        if !is_checked() {
+            return Num(value.as_());
+        }
+        match T::from(value) {
+            Some(v) => Num(v),
+            None => overflow(format!(
+                "{} as {}: {}",
+                short_type_name::<U>(),
+                short_type_name::<T>(),
+                value
+            )),
+        }
+    }
+
+    fn checked(
+        self,
+        rhs: Self,
+        symbol: &str,
+        checked: fn(&T, &T) -> Option<T>,
+        plain: fn(T, T) -> T,
+    ) -> Self {
+        if !is_checked() {
+            return Num(plain(self.0, rhs.0));
+        }
+        match checked(&self.0, &rhs.0) {
+            Some(v) => Num(v),
+            None => overflow(format!(
+                "{}: {} {} {}",
+                short_type_name::<T>(),
+                self.0,
+                symbol,
+                rhs.0
+            )),
+        }
+    }
+}
+
+impl<T: PrimInt + fmt::Display> Add for Num<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.checked(rhs, "+", T::checked_add, |a, b| a + b)
+    }
+}
+
+impl<T: PrimInt + fmt::Display> Sub for Num<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.checked(rhs, "-", T::checked_sub, |a, b| a - b)
+    }
+}
+
+impl<T: PrimInt + fmt::Display> Mul for Num<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked(rhs, "*", T::checked_mul, |a, b| a * b)
+    }
+}
+
+impl<T: PrimInt + fmt::Display> AddAssign for Num<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: PrimInt + fmt::Display> SubAssign for Num<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: PrimInt + fmt::Display> MulAssign for Num<T> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: PrimInt + fmt::Display> Sum for Num<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Num(T::zero()), |total, n| total + n)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Num<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_operation_that_overflows() {
+        let _checked = checked_scope(true);
+        assert_eq!(catch_overflow(|| Num(2i32) * Num(3) - Num(1)), Ok(Num(5)));
+        let sum = catch_overflow(|| [Num(i32::MAX), Num(1)].into_iter().sum::<Num<i32>>());
+        assert_eq!(sum.unwrap_err().to_string(), "i32: 2147483647 + 1 overflows");
+        let difference = catch_overflow(|| Num(1517886983i32) - Num(-1517886983));
+        assert_eq!(
+            difference.unwrap_err().operation,
+            "i32: 1517886983 - -1517886983"
+        );
+        let narrowed = catch_overflow(|| Num::<i32>::from_int(5_000_000_000i64));
+        assert_eq!(narrowed.unwrap_err().operation, "i64 as i32: 5000000000");
+        assert_eq!(Num::<u8>::from_int(200u64), Num(200));
+    }
+
+    #[test]
+    fn other_panics_are_passed_on() {
+        let result = panic::catch_unwind(|| catch_overflow(|| panic!("not an overflow")));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Bounded parallel map and reduce for the solvers.
+//!
+//! Work is shared out to at most `threads()` workers, the thread count set with
+//! `--threads N`, `AOC_THREADS` or `aoc.toml`. With one thread every item runs
+//! in order on the calling thread, for deterministic debugging. Results always
+//! come back in the order of the items. A worker that panics stops the others
+//! from taking more items and its panic comes back as a `WorkerPanic`; solvers
+//! pass it on with `WorkerPanic::raise`, which `runner::try_solve_part` turns
+//! into an `AocError` for the day and part.
+
+use crate::config;
+use crate::fuzz::panic_message;
+use crate::numeric::Overflow;
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread;
+
+static QUIET_HOOK: Once = Once::new();
+
+/// How many workers `map` uses at most.
+pub fn threads() -> usize {
+    config::current().threads.max(1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerPanic {
+    pub message: String,
+}
+
+impl fmt::Display for WorkerPanic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a worker panicked: {}", self.message)
+    }
+}
+
+impl Error for WorkerPanic {}
+
+impl WorkerPanic {
+    /// Unwinds with this error, for solvers that can only return an answer.
+    pub fn raise(self) -> ! {
+        // The worker's own panic was reported already
+        QUIET_HOOK.call_once(|| {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !info.payload().is::<WorkerPanic>() {
+                    default_hook(info);
+                }
+            }));
+        });
+        panic::panic_any(self)
+    }
+}
+
+/// Runs `f`, returning the `WorkerPanic` it was raised with, if any. Other
+/// panics are passed on.
+pub fn catch_worker_panic<R>(f: impl FnOnce() -> R) -> Result<R, WorkerPanic> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => Ok(value),
+        Err(payload) => match payload.downcast::<WorkerPanic>() {
+            Ok(worker_panic) => Err(*worker_panic),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// `f` of every item, in the order of `items`.
+pub fn map<T, R, F>(items: impl IntoIterator<Item = T>, f: F) -> Result<Vec<R>, WorkerPanic>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    map_on(threads(), items, f)
+}
+
+// `map` with at most `threads` workers
+fn map_on<T, R, F>(
+    threads: usize,
+    items: impl IntoIterator<Item = T>,
+    f: F,
+) -> Result<Vec<R>, WorkerPanic>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let items: Vec<T> = items.into_iter().collect();
+    let workers = threads.min(items.len());
+    if workers <= 1 {
+        return items.into_iter().map(|item| run(&f, item)).collect();
+    }
+
+    let len = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let stop = AtomicBool::new(false);
+    let worker = || {
+        let mut done = Vec::new();
+        while !stop.load(Ordering::Relaxed) {
+            let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+            let Some((i, item)) = next else {
+                break;
+            };
+            match run(&f, item) {
+                Ok(res) => done.push((i, res)),
+                Err(e) => {
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(done)
+    };
+    let finished: Vec<Result<Vec<(usize, R)>, WorkerPanic>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(worker)).collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_else(|p| panic::resume_unwind(p)))
+            .collect()
+    });
+
+    let mut results: Vec<Option<R>> = (0..len).map(|_| None).collect();
+    for done in finished {
+        for (i, res) in done? {
+            results[i] = Some(res);
+        }
+    }
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// `f` of every item, combined with `combine` in the order of `items`, or
+/// `None` without items.
+pub fn map_reduce<T, R, F, G>(
+    items: impl IntoIterator<Item = T>,
+    f: F,
+    combine: G,
+) -> Result<Option<R>, WorkerPanic>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    G: FnMut(R, R) -> R,
+{
+    Ok(map(items, f)?.into_iter().reduce(combine))
+}
+
+fn run<T, R>(f: &impl Fn(T) -> R, item: T) -> Result<R, WorkerPanic> {
+    match panic::catch_unwind(AssertUnwindSafe(|| f(item))) {
+        Ok(res) => Ok(res),
+        Err(payload) => {
+            // Overflows are errors of their own, see `numeric`, and a nested
+            // map has made its panic an error already
+            if payload.is::<Overflow>() || payload.is::<WorkerPanic>() {
+                panic::resume_unwind(payload);
+            }
+            Err(WorkerPanic {
+                message: panic_message(payload.as_ref()),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aoc::{Aoc, AocError, AocRes};
+    use crate::numeric::{self, Num};
+    use crate::runner::try_solve_part;
+
+    #[test]
+    fn keeps_the_order_of_the_items() {
+        let squares = map(0..100u64, |n| n * n).unwrap();
+        assert_eq!(squares, (0..100u64).map(|n| n * n).collect::<Vec<_>>());
+        assert_eq!(map_reduce(1..=10, |n| n, |a, b| a + b), Ok(Some(55)));
+        assert_eq!(map_reduce(Vec::<u32>::new(), |n| n, u32::min), Ok(None));
+    }
+
+    #[test]
+    fn one_thread_runs_in_order_on_the_caller() {
+        let caller = thread::current().id();
+        let order = Mutex::new(Vec::new());
+        let res = map_on(1, 0..20, |n| {
+            order.lock().unwrap().push(n);
+            thread::current().id() == caller
+        });
+        assert_eq!(res.unwrap(), [true; 20]);
+        assert_eq!(order.into_inner().unwrap(), (0..20).collect::<Vec<_>>());
+    }
+
+    // Adds up past i32::MAX on several workers
+    struct Overflowing;
+
+    impl Aoc for Overflowing {
+        fn new(_: &str) -> Self {
+            Overflowing
+        }
+
+        fn part1(&self) -> AocRes {
+            let sums =
+                map_on(4, 0..50, |n| Num(i32::MAX - 10) + Num(n)).unwrap_or_else(|e| e.raise());
+            AocRes::Int32(sums.into_iter().sum::<Num<i32>>().get())
+        }
+
+        fn part2(&self) -> AocRes {
+            AocRes::Int32(0)
+        }
+    }
+
+    #[test]
+    fn overflows_on_other_threads_are_errors_of_their_own() {
+        let _checked = numeric::checked_scope(true);
+        let res = try_solve_part(&Overflowing, 1, 1);
+        let Err(AocError::Overflow {
+            day,
+            part,
+            operation,
+        }) = res
+        else {
+            panic!("expected an overflow, got {:?}", res);
+        };
+        assert_eq!((day, part), (1, 1));
+        assert!(operation.starts_with("i32: 2147483637 + "), "{}", operation);
+    }
+
+    #[test]
+    fn returns_worker_panics_as_errors() {
+        let res = map(0..50, |n| {
+            if n == 7 {
+                panic!("no sevens");
+            }
+            n
+        });
+        assert_eq!(res.unwrap_err().to_string(), "a worker panicked: no sevens");
+        let raised = catch_worker_panic(|| {
+            map([1], |_: i32| -> i32 { panic!("boom") }).unwrap_or_else(|e| e.raise())
+        });
+        assert_eq!(raised.unwrap_err().message, "boom");
+    }
+}
@@ -0,0 +1,244 @@
+//! Loading of solution plugins built as `cdylib` shared objects.
+//!
+//! A plugin exports a single `extern "C"` function named `aoc_plugin` that
+//! returns a pointer to a static `AocPluginInfo`. Everything crossing the
+//! boundary is `#[repr(C)]`, so plugins only need to agree on
+//! `AOC_PLUGIN_ABI_VERSION`, not on the compiler version.
+//! The easiest way to write one is the `export_aoc_plugin!` macro.
+
+use crate::aoc::{AocError, AocRes};
+use libloading::{Library, Symbol};
+use std::ffi::{c_char, CStr, CString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const AOC_PLUGIN_ABI_VERSION: u32 = 1;
+pub const AOC_PLUGIN_SYMBOL: &[u8] = b"aoc_plugin\0";
+
+pub const AOC_PLUGIN_OK: i32 = 0;
+pub const AOC_PLUGIN_PANICKED: i32 = 1;
+pub const AOC_PLUGIN_BAD_ARGS: i32 = 2;
+
+/// `AocRes` as it crosses the C ABI: a tag plus the raw bits of the value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AocPluginAnswer {
+    pub status: i32,
+    pub kind: u32,
+    pub bits: u64,
+}
+
+/// Solves `part` (1 or 2) of the plugin's day on the NUL-terminated path `path_to_input`.
+pub type AocPluginSolveFn = extern "C" fn(part: u32, path_to_input: *const c_char) -> AocPluginAnswer;
+
+#[repr(C)]
+pub struct AocPluginInfo {
+    pub abi_version: u32,
+    pub day: u32,
+    /// NUL-terminated and valid for as long as the library is loaded
+    pub title: *const c_char,
+    pub solve: AocPluginSolveFn,
+}
+
+// The info block only holds pointers into the plugin's static data
+unsafe impl Sync for AocPluginInfo {}
+
+pub type AocPluginEntryFn = extern "C" fn() -> *const AocPluginInfo;
+
+impl AocPluginAnswer {
+    pub fn from_res(res: AocRes) -> Self {
+        let (kind, bits) = match res {
+            AocRes::Int32(data) => (0, data as i64 as u64),
+            AocRes::Int64(data) => (1, data as u64),
+            AocRes::UInt32(data) => (2, data as u64),
+            AocRes::UInt64(data) => (3, data),
+        };
+        Self {
+            status: AOC_PLUGIN_OK,
+            kind,
+            bits,
+        }
+    }
+
+    pub fn from_status(status: i32) -> Self {
+        Self {
+            status,
+            kind: 0,
+            bits: 0,
+        }
+    }
+
+    pub fn to_res(self) -> Option<AocRes> {
+        if self.status != AOC_PLUGIN_OK {
+            return None;
+        }
+        match self.kind {
+            0 => Some(AocRes::Int32(self.bits as i64 as i32)),
+            1 => Some(AocRes::Int64(self.bits as i64)),
+            2 => Some(AocRes::UInt32(self.bits as u32)),
+            3 => Some(AocRes::UInt64(self.bits)),
+            _ => None,
+        }
+    }
+}
+
+/// A loaded plugin. The library stays open for as long as this lives.
+pub struct Plugin {
+    pub path: PathBuf,
+    pub day: u32,
+    pub title: String,
+    solve: AocPluginSolveFn,
+    _lib: Library,
+}
+
+impl Plugin {
+    pub fn load<P>(path: P) -> Result<Self, AocError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let err = |reason: String| AocError::Plugin {
+            path: path.clone(),
+            reason,
+        };
+        // Loading runs the library's initialisers and trusts its exported entry point,
+        // so only point the runner at directories holding plugins you built yourself.
+        let lib = unsafe { Library::new(&path) }.map_err(|e| err(e.to_string()))?;
+        let info = unsafe {
+            let entry: Symbol<AocPluginEntryFn> = lib
+                .get(AOC_PLUGIN_SYMBOL)
+                .map_err(|e| err(e.to_string()))?;
+            entry()
+        };
+        let Some(info) = (unsafe { info.as_ref() }) else {
+            return Err(err("entry point returned null".to_string()));
+        };
+        if info.abi_version != AOC_PLUGIN_ABI_VERSION {
+            return Err(err(format!(
+                "ABI version {} is not supported (expected {})",
+                info.abi_version, AOC_PLUGIN_ABI_VERSION
+            )));
+        }
+        let title = if info.title.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(info.title) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        Ok(Self {
+            day: info.day,
+            title,
+            solve: info.solve,
+            path,
+            _lib: lib,
+        })
+    }
+
+    pub fn solve(&self, part: u32, path_to_input: &str) -> Result<AocRes, AocError> {
+        let err = |reason: String| AocError::Plugin {
+            path: self.path.clone(),
+            reason,
+        };
+        let c_path = CString::new(path_to_input).map_err(|e| err(e.to_string()))?;
+        let answer = (self.solve)(part, c_path.as_ptr());
+        match answer.status {
+            AOC_PLUGIN_OK => answer
+                .to_res()
+                .ok_or_else(|| err(format!("unknown answer kind {}", answer.kind))),
+            AOC_PLUGIN_PANICKED => Err(err(format!("part {} panicked", part))),
+            AOC_PLUGIN_BAD_ARGS => Err(err(format!("part {} rejected its arguments", part))),
+            status => Err(err(format!("part {} failed with status {}", part, status))),
+        }
+    }
+}
+
+/// Loads every shared object in `dir`. Files that fail to load are returned as errors
+/// so the caller can report them without giving up on the rest.
+/// A plugin reporting the same day and title as one already loaded is skipped, since
+/// cargo leaves a hashed copy of each `cdylib` next to the plain one.
+pub fn load_plugins<P>(dir: P) -> Result<Vec<Result<Plugin, AocError>>, AocError>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+    let entries = fs::read_dir(dir).map_err(|e| AocError::Plugin {
+        path: dir.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+    let mut plugins = Vec::<Result<Plugin, AocError>>::new();
+    for path in paths {
+        let plugin = Plugin::load(path);
+        if let Ok(p) = &plugin {
+            let duplicate = plugins.iter().any(|other| match other {
+                Ok(other) => other.day == p.day && other.title == p.title,
+                Err(_) => false,
+            });
+            if duplicate {
+                continue;
+            }
+        }
+        plugins.push(plugin);
+    }
+    Ok(plugins)
+}
+
+/// Exports an `Aoc` implementation as a plugin. Use it from a crate built with
+/// `crate-type = ["cdylib"]`:
+///
+/// ```ignore
+/// aoc::export_aoc_plugin!(7, "Camel Cards (sorted by key)", MyDay7);
+/// ```
+#[macro_export]
+macro_rules! export_aoc_plugin {
+    ($day:expr, $title:expr, $solver:ty) => {
+        extern "C" fn __aoc_plugin_solve(
+            part: u32,
+            path_to_input: *const ::std::ffi::c_char,
+        ) -> $crate::plugin::AocPluginAnswer {
+            use $crate::plugin::{AocPluginAnswer, AOC_PLUGIN_BAD_ARGS, AOC_PLUGIN_PANICKED};
+            use $crate::Aoc;
+            if path_to_input.is_null() {
+                return AocPluginAnswer::from_status(AOC_PLUGIN_BAD_ARGS);
+            }
+            let path = unsafe { ::std::ffi::CStr::from_ptr(path_to_input) };
+            let Ok(path) = path.to_str() else {
+                return AocPluginAnswer::from_status(AOC_PLUGIN_BAD_ARGS);
+            };
+            let res = ::std::panic::catch_unwind(|| {
+                let solver = <$solver>::new(path);
+                match part {
+                    1 => Some(solver.part1()),
+                    2 => Some(solver.part2()),
+                    _ => None,
+                }
+            });
+            match res {
+                Ok(Some(res)) => AocPluginAnswer::from_res(res),
+                Ok(None) => AocPluginAnswer::from_status(AOC_PLUGIN_BAD_ARGS),
+                Err(_) => AocPluginAnswer::from_status(AOC_PLUGIN_PANICKED),
+            }
+        }
+
+        static __AOC_PLUGIN_INFO: $crate::plugin::AocPluginInfo = $crate::plugin::AocPluginInfo {
+            abi_version: $crate::plugin::AOC_PLUGIN_ABI_VERSION,
+            day: $day,
+            title: concat!($title, "\0").as_ptr() as *const ::std::ffi::c_char,
+            solve: __aoc_plugin_solve,
+        };
+
+        #[no_mangle]
+        pub extern "C" fn aoc_plugin() -> *const $crate::plugin::AocPluginInfo {
+            &__AOC_PLUGIN_INFO
+        }
+    };
+}
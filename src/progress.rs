@@ -0,0 +1,237 @@
+//! Progress reporting for long-running parts.
+//!
+//! A solver grabs `Progress::current()` once and reports to it from its loop,
+//! either a position out of a known total or a plain count when the total isn't
+//! known up front. The runner installs a tracker on its own thread around every
+//! part it solves and draws a bar on stderr, but only when stderr is a terminal.
+//! Outside of the runner `Progress::current()` is a no-op.
+
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Parts finishing sooner than this never show a bar.
+const BAR_DELAY: Duration = Duration::from_millis(200);
+const BAR_WIDTH: usize = 30;
+
+static BAR_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<State>>> = const { RefCell::new(None) };
+}
+
+struct State {
+    current: AtomicU64,
+    // 0 until the solver sets one
+    total: AtomicU64,
+    done: AtomicBool,
+    started: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    pub current: u64,
+    pub total: Option<u64>,
+    pub elapsed: Duration,
+}
+
+impl State {
+    fn snapshot(&self) -> Snapshot {
+        let total = self.total.load(Ordering::Relaxed);
+        Snapshot {
+            current: self.current.load(Ordering::Relaxed),
+            total: (total > 0).then_some(total),
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Progress {
+    state: Option<Arc<State>>,
+}
+
+impl Progress {
+    /// The tracker the runner installed on this thread, or one that ignores everything.
+    pub fn current() -> Self {
+        Self {
+            state: CURRENT.with(|c| c.borrow().clone()),
+        }
+    }
+
+    pub fn set_total(&self, total: u64) {
+        if let Some(state) = &self.state {
+            state.total.store(total, Ordering::Relaxed);
+        }
+    }
+
+    pub fn set(&self, current: u64) {
+        if let Some(state) = &self.state {
+            state.current.store(current, Ordering::Relaxed);
+        }
+    }
+
+    pub fn add(&self, n: u64) {
+        if let Some(state) = &self.state {
+            state.current.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+}
+
+// Uninstalls the tracker and stops the bar, also when the part panics
+struct Installed {
+    state: Arc<State>,
+    previous: Option<Arc<State>>,
+}
+
+impl Installed {
+    fn new() -> Self {
+        let state = Arc::new(State {
+            current: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+            started: Instant::now(),
+        });
+        let previous = CURRENT.with(|c| c.replace(Some(Arc::clone(&state))));
+        Self { state, previous }
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        self.state.done.store(true, Ordering::Relaxed);
+        CURRENT.with(|c| *c.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Runs `f` with a tracker installed and returns what was last reported to it.
+pub fn track<T>(f: impl FnOnce() -> T) -> (T, Snapshot) {
+    let installed = Installed::new();
+    let res = f();
+    (res, installed.state.snapshot())
+}
+
+/// Runs `f` with a tracker installed, drawing it as a bar on stderr while it runs.
+pub fn with_progress<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    let installed = Installed::new();
+    let bar = bar_visible().then(|| {
+        let state = Arc::clone(&installed.state);
+        let label = label.to_string();
+        thread::spawn(move || draw_until_done(&label, &state))
+    });
+    let res = f();
+    drop(installed);
+    if let Some(bar) = bar {
+        let _ = bar.join();
+    }
+    res
+}
+
+/// Hides the bar even on a terminal, for front ends that own the screen.
+pub fn suppress_bar(suppressed: bool) {
+    BAR_SUPPRESSED.store(suppressed, Ordering::Relaxed);
+}
+
+fn bar_visible() -> bool {
+    !BAR_SUPPRESSED.load(Ordering::Relaxed) && io::stderr().is_terminal()
+}
+
+fn draw_until_done(label: &str, state: &State) {
+    let mut drawn = false;
+    while !state.done.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(100));
+        let snapshot = state.snapshot();
+        if snapshot.elapsed >= BAR_DELAY && !state.done.load(Ordering::Relaxed) {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r{}\x1b[K", render_line(label, &snapshot));
+            let _ = stderr.flush();
+            drawn = true;
+        }
+    }
+    if drawn {
+        let _ = write!(io::stderr(), "\r\x1b[K");
+    }
+}
+
+/// One line of progress: a bar when the total is known, a count and rate otherwise.
+pub fn render_line(label: &str, snapshot: &Snapshot) -> String {
+    match snapshot.total {
+        Some(total) => {
+            let fraction = (snapshot.current as f64 / total as f64).clamp(0.0, 1.0);
+            let filled = (fraction * BAR_WIDTH as f64) as usize;
+            format!(
+                "{} [{}{}] {:>3}% {}/{}",
+                label,
+                "#".repeat(filled),
+                ".".repeat(BAR_WIDTH - filled),
+                (fraction * 100.0) as u32,
+                snapshot.current,
+                total
+            )
+        }
+        None => {
+            let seconds = snapshot.elapsed.as_secs_f64().max(1e-3);
+            format!(
+                "{} {} ({}/s)",
+                label,
+                snapshot.current,
+                human(snapshot.current as f64 / seconds)
+            )
+        }
+    }
+}
+
+fn human(rate: f64) -> String {
+    if rate >= 1e9 {
+        format!("{:.1}G", rate / 1e9)
+    } else if rate >= 1e6 {
+        format!("{:.1}M", rate / 1e6)
+    } else if rate >= 1e3 {
+        format!("{:.1}k", rate / 1e3)
+    } else {
+        format!("{:.0}", rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_bars_and_rates() {
+        let snapshot = Snapshot {
+            current: 15,
+            total: Some(60),
+            elapsed: Duration::from_secs(1),
+        };
+        assert_eq!(
+            render_line("day6 part2", &snapshot),
+            "day6 part2 [#######.......................]  25% 15/60"
+        );
+        let snapshot = Snapshot {
+            current: 2_500_000,
+            total: None,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(
+            render_line("day8 part1", &snapshot),
+            "day8 part1 2500000 (1.2M/s)"
+        );
+    }
+
+    #[test]
+    fn reports_only_while_tracked() {
+        Progress::current().add(5);
+        let ((), snapshot) = track(|| {
+            let progress = Progress::current();
+            progress.set_total(10);
+            progress.add(3);
+            progress.add(4);
+        });
+        assert_eq!((snapshot.current, snapshot.total), (7, Some(10)));
+        assert!(Progress::current().state.is_none());
+    }
+}
@@ -0,0 +1,307 @@
+//! Interactive shell for poking at one day at a time.
+//!
+//! The selected day's solver and the text of its input are kept around between
+//! commands, the text in memory through `input::keep`, so switching parts or
+//! inputs doesn't require going through `main.rs` again. Each part still parses
+//! the text itself. A command that panics is reported as an error and the shell
+//! carries on.
+
+use crate::aoc::Aoc;
+use crate::cache::{CacheKey, ResultCache};
+use crate::config;
+use crate::fuzz::panic_message;
+use crate::input;
+use crate::runner::{run_part, PartRun};
+use crate::{input_path_for_day, solver_for_day, title_for_day, NUM_DAYS};
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+const HELP: &str = "\
+commands:
+  day N         select day N and its puzzle input
+  input PATH    solve the current day on PATH instead
+  part N        select part N and solve it
+  run           solve the current part (answers may come from the cache)
+  time          solve the current part without the cache and report how long it took
+  verify        solve the current part without the cache and compare with the cached answer
+  show          print the current day, input, part and last answers
+  help          print this message
+  quit          leave the shell";
+
+pub enum Outcome {
+    Continue(String),
+    Quit,
+}
+
+pub struct Repl {
+    day: Option<u32>,
+    path_to_input: String,
+    part: u32,
+    solver: Option<Box<dyn Aoc>>,
+    last_runs: [Option<PartRun>; 2],
+    cache: ResultCache,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new(ResultCache::new(&config::current().cache))
+    }
+}
+
+impl Repl {
+    pub fn new(cache: ResultCache) -> Self {
+        Self {
+            day: None,
+            path_to_input: String::new(),
+            part: 1,
+            solver: None,
+            last_runs: [None, None],
+            cache,
+        }
+    }
+
+    pub fn run<R, W>(&mut self, input: R, mut output: W) -> io::Result<()>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        write!(output, "{}", self.prompt())?;
+        output.flush()?;
+        for line in input.lines() {
+            match self.execute(&line?) {
+                Ok(Outcome::Continue(msg)) if msg.is_empty() => (),
+                Ok(Outcome::Continue(msg)) => writeln!(output, "{}", msg)?,
+                Ok(Outcome::Quit) => return Ok(()),
+                Err(e) => writeln!(output, "error: {}", e)?,
+            }
+            write!(output, "{}", self.prompt())?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+
+    fn prompt(&self) -> String {
+        match self.day {
+            Some(day) => format!("day{} part{}> ", day, self.part),
+            None => "aoc> ".to_string(),
+        }
+    }
+
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, String> {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.dispatch(line))) {
+            Ok(outcome) => outcome,
+            Err(payload) => Err(format!(
+                "'{}' panicked: {}",
+                line.trim(),
+                panic_message(payload.as_ref())
+            )),
+        }
+    }
+
+    fn dispatch(&mut self, line: &str) -> Result<Outcome, String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Outcome::Continue(String::new()));
+        };
+        let arg = words.next();
+        let msg = match (command, arg) {
+            ("day", Some(n)) => self.select_day(n)?,
+            ("input", Some(path)) => self.select_input(path)?,
+            ("part", Some(n)) => {
+                self.part = match n {
+                    "1" => 1,
+                    "2" => 2,
+                    _ => return Err(format!("there is no part {}", n)),
+                };
+                self.solve(true)?
+            }
+            ("run", None) => self.solve(true)?,
+            ("time", None) => {
+                let run = self.solve_run(false)?;
+                format!("{} ({:.3?})", run.answer, run.duration)
+            }
+            ("verify", None) => self.verify()?,
+            ("show", None) => self.show(),
+            ("help", None) => HELP.to_string(),
+            ("quit", None) | ("exit", None) => return Ok(Outcome::Quit),
+            _ => return Err(format!("unknown command '{}', try 'help'", line.trim())),
+        };
+        Ok(Outcome::Continue(msg))
+    }
+
+    fn select_day(&mut self, n: &str) -> Result<String, String> {
+        let day = match n.parse::<u32>() {
+            Ok(day) if (1..=NUM_DAYS).contains(&day) => day,
+            _ => return Err(format!("no such day: {}", n)),
+        };
+        self.switch_day(day, input_path_for_day(day))
+    }
+
+    // Only changes the day once its input could be loaded
+    fn switch_day(&mut self, day: u32, path_to_input: String) -> Result<String, String> {
+        self.load(day, path_to_input)?;
+        self.day = Some(day);
+        self.part = 1;
+        Ok(format!("day {}: {}", day, title_for_day(day).unwrap_or("")))
+    }
+
+    fn select_input(&mut self, path: &str) -> Result<String, String> {
+        let day = self.day.ok_or("select a day first")?;
+        self.load(day, path.to_string())?;
+        Ok(format!("using {}", path))
+    }
+
+    fn load(&mut self, day: u32, path_to_input: String) -> Result<(), String> {
+        if !Path::new(&path_to_input).is_file() || input::keep(&path_to_input).is_err() {
+            return Err(format!("cannot read {}", path_to_input));
+        }
+        if self.path_to_input != path_to_input {
+            input::release(&self.path_to_input);
+        }
+        self.solver = solver_for_day(day, &path_to_input);
+        self.path_to_input = path_to_input;
+        self.last_runs = [None, None];
+        Ok(())
+    }
+
+    fn solve(&mut self, use_cache: bool) -> Result<String, String> {
+        let run = self.solve_run(use_cache)?;
+        Ok(if run.cached {
+            format!("{} (cached)", run.answer)
+        } else {
+            run.answer.to_string()
+        })
+    }
+
+    fn solve_run(&mut self, use_cache: bool) -> Result<&PartRun, String> {
+        let (Some(day), Some(solver)) = (self.day, &self.solver) else {
+            return Err("select a day first".to_string());
+        };
+        let cache = use_cache.then_some(&self.cache);
+        let run = run_part(solver.as_ref(), day, self.part, &self.path_to_input, cache)
+            .map_err(|e| e.to_string())?;
+        let slot = &mut self.last_runs[self.part as usize - 1];
+        Ok(slot.insert(run))
+    }
+
+    fn verify(&mut self) -> Result<String, String> {
+        let (Some(day), Some(solver)) = (self.day, &self.solver) else {
+            return Err("select a day first".to_string());
+        };
+        let text = input::read_input(&self.path_to_input).map_err(|e| e.to_string())?;
+        let key = CacheKey::new(day, self.part, solver.version(), text.as_bytes());
+        let expected = self.cache.get(&key);
+        let answer = self.solve_run(false)?.answer;
+        Ok(match expected {
+            Some(expected) if expected == answer => format!("{} matches the cached answer", answer),
+            Some(expected) => format!("{} DIFFERS from the cached answer {}", answer, expected),
+            None => format!("{} (no cached answer to compare with)", answer),
+        })
+    }
+
+    fn show(&self) -> String {
+        let Some(day) = self.day else {
+            return "no day selected".to_string();
+        };
+        let mut lines = vec![
+            format!("day:   {} ({})", day, title_for_day(day).unwrap_or("")),
+            format!("input: {}", self.path_to_input),
+            format!("part:  {}", self.part),
+        ];
+        for (i, run) in self.last_runs.iter().enumerate() {
+            let answer = match run {
+                Some(run) => format!("{} ({:.3?})", run.answer, run.duration),
+                None => "-".to_string(),
+            };
+            lines.push(format!("part{}: {}", i + 1, answer));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Drop for Repl {
+    fn drop(&mut self) {
+        input::release(&self.path_to_input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn output(repl: &mut Repl, line: &str) -> String {
+        match repl.execute(line) {
+            Ok(Outcome::Continue(msg)) => msg,
+            Ok(Outcome::Quit) => "<quit>".to_string(),
+            Err(e) => format!("error: {}", e),
+        }
+    }
+
+    #[test]
+    fn keeps_day_and_input_between_commands() {
+        let dir = std::env::temp_dir().join(format!("aoc-repl-test-{}", std::process::id()));
+        let mut repl = Repl::new(ResultCache::new(&dir));
+        assert_eq!(output(&mut repl, "run"), "error: select a day first");
+        assert_eq!(output(&mut repl, "day 8"), "day 8: Haunted Wasteland");
+        assert_eq!(output(&mut repl, "input ./inputs/day8_test.input"), "using ./inputs/day8_test.input");
+        assert_eq!(output(&mut repl, "part 1"), "6");
+        assert_eq!(output(&mut repl, "run"), "6 (cached)");
+        assert_eq!(output(&mut repl, "verify"), "6 matches the cached answer");
+        assert!(output(&mut repl, "show").contains("part1: 6"));
+        assert_eq!(output(&mut repl, "part 3"), "error: there is no part 3");
+        assert_eq!(output(&mut repl, "quit"), "<quit>");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejects_unknown_days_and_missing_inputs() {
+        let mut repl = Repl::new(ResultCache::new(std::env::temp_dir()));
+        assert_eq!(output(&mut repl, "day 26"), "error: no such day: 26");
+        assert_eq!(output(&mut repl, "input foo"), "error: select a day first");
+        output(&mut repl, "day 8");
+        assert_eq!(output(&mut repl, "input ./inputs/nope"), "error: cannot read ./inputs/nope");
+        assert!(output(&mut repl, "dance").starts_with("error: unknown command"));
+    }
+
+    #[test]
+    fn keeps_the_loaded_day_when_another_cannot_be_loaded() {
+        let mut repl = Repl::new(ResultCache::new(std::env::temp_dir()));
+        output(&mut repl, "day 8");
+        output(&mut repl, "input ./inputs/day8_test.input");
+        let missing = repl.switch_day(9, "./inputs/nope".to_string());
+        assert_eq!(missing.unwrap_err(), "cannot read ./inputs/nope");
+        assert_eq!(repl.prompt(), "day8 part1> ");
+        assert_eq!(output(&mut repl, "time").split(' ').next(), Some("6"));
+    }
+
+    #[test]
+    fn caches_answers_under_the_text_the_solver_read() {
+        let dir = std::env::temp_dir().join(format!("aoc-repl-edit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("day8.input");
+        fs::copy("./inputs/day8_test.input", &path).unwrap();
+        let mut repl = Repl::new(ResultCache::new(dir.join("cache")));
+        output(&mut repl, "day 8");
+        output(&mut repl, &format!("input {}", path.display()));
+        assert_eq!(output(&mut repl, "run"), "6");
+        // The shell goes on with the text it loaded, whatever happens to the file
+        fs::write(&path, "L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        assert_eq!(output(&mut repl, "run"), "6 (cached)");
+        assert_eq!(output(&mut repl, "verify"), "6 matches the cached answer");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn survives_panicking_parts() {
+        let path = std::env::temp_dir().join(format!("aoc-repl-panic-{}", std::process::id()));
+        fs::write(&path, "not a hand\n").unwrap();
+        let mut repl = Repl::new(ResultCache::new(std::env::temp_dir()));
+        output(&mut repl, "day 7");
+        output(&mut repl, &format!("input {}", path.display()));
+        assert!(output(&mut repl, "time").starts_with("error: 'time' panicked"));
+        assert_eq!(output(&mut repl, "help"), HELP);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,157 @@
+//! Running a single part of a day, shared by the command line front ends.
+
+use crate::aoc::{Aoc, AocError, AocRes};
+use crate::cache::{input_hash, CacheKey, ResultCache};
+use crate::history;
+use crate::input;
+use crate::numeric::catch_overflow;
+use crate::parallel::catch_worker_panic;
+use crate::progress::with_progress;
+use crate::solver_for_day;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct PartRun {
+    pub day: u32,
+    pub part: u32,
+    pub answer: AocRes,
+    pub cached: bool,
+    pub duration: Duration,
+}
+
+impl PartRun {
+    /// One JSON object, like `{"day": 6, "part": 2, "answer": 29891250, "cached": false, "seconds": 0.012}`.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"day\": {}, \"part\": {}, \"answer\": {}, \"cached\": {}, \"seconds\": {}}}",
+            self.day,
+            self.part,
+            self.answer,
+            self.cached,
+            self.duration.as_secs_f64()
+        )
+    }
+}
+
+pub fn solve_part(solver: &dyn Aoc, part: u32) -> AocRes {
+    match part {
+        1 => solver.part1(),
+        2 => solver.part2(),
+        _ => panic!("there is no part {}!", part),
+    }
+}
+
+/// `solve_part`, with an overflow in checked arithmetic or a panic in a
+/// `parallel` worker as an error.
+pub fn try_solve_part(solver: &dyn Aoc, day: u32, part: u32) -> Result<AocRes, AocError> {
+    match catch_overflow(|| catch_worker_panic(|| solve_part(solver, part))) {
+        Ok(Ok(answer)) => Ok(answer),
+        Ok(Err(worker_panic)) => Err(AocError::Worker {
+            day,
+            part,
+            message: worker_panic.message,
+        }),
+        Err(overflow) => Err(AocError::Overflow {
+            day,
+            part,
+            operation: overflow.operation,
+        }),
+    }
+}
+
+/// Runs `part` of `day`, answering from `cache` when it already holds a result
+/// for the same input and solver version. Parts that did run are logged to the
+/// history.
+pub fn run_part(
+    solver: &dyn Aoc,
+    day: u32,
+    part: u32,
+    path_to_input: &str,
+    cache: Option<&ResultCache>,
+) -> Result<PartRun, AocError> {
+    let start = Instant::now();
+    // Keyed on the text the solver reads, which the REPL may keep in memory. Without a
+    // readable input there is nothing to key on, the solver reports the error
+    let text = input::read_input(path_to_input).ok();
+    let key = match (cache, &text) {
+        (Some(_), Some(text)) => Some(CacheKey::new(day, part, solver.version(), text.as_bytes())),
+        _ => None,
+    };
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Some(answer) = cache.get(key) {
+            return Ok(PartRun {
+                day,
+                part,
+                answer,
+                cached: true,
+                duration: start.elapsed(),
+            });
+        }
+    }
+
+    let label = format!("day{} part{}", day, part);
+    let answer = with_progress(&label, || try_solve_part(solver, day, part))?;
+    let duration = start.elapsed();
+    if let Some(text) = &text {
+        history::record(day, part, answer, duration, input_hash(text.as_bytes()));
+    }
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Err(e) = cache.put(key, answer) {
+            eprintln!("could not cache day{} part{}: {}", day, part, e);
+        }
+    }
+    Ok(PartRun {
+        day,
+        part,
+        answer,
+        cached: false,
+        duration,
+    })
+}
+
+/// `run_part` with a solver of its own built on another thread, giving up after
+/// `timeout`. A part that gives up is left running until the process exits,
+/// there is no way to stop a thread from outside.
+pub fn run_part_within(
+    day: u32,
+    part: u32,
+    path_to_input: &str,
+    cache: Option<&ResultCache>,
+    timeout: Duration,
+) -> Result<PartRun, AocError> {
+    let path_to_input = path_to_input.to_string();
+    let cache = cache.cloned();
+    run_within(day, part, timeout, move || {
+        let Some(solver) = solver_for_day(day, &path_to_input) else {
+            panic!("there is no day {}!", day);
+        };
+        run_part(solver.as_ref(), day, part, &path_to_input, cache.as_ref())
+    })
+}
+
+/// Runs `solve` for `part` of `day` on a thread of its own, giving up after
+/// `timeout`. Whatever `solve` owns stays alive until it returns, even once
+/// nobody waits for it any more.
+pub fn run_within<F>(day: u32, part: u32, timeout: Duration, solve: F) -> Result<PartRun, AocError>
+where
+    F: FnOnce() -> Result<PartRun, AocError> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let run = panic::catch_unwind(AssertUnwindSafe(solve));
+        let _ = tx.send(run);
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(run)) => run,
+        // Panics are passed on like `run_part` would
+        Ok(Err(payload)) => panic::resume_unwind(payload),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(AocError::Timeout {
+            day,
+            part,
+            after: timeout,
+        }),
+        Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!("the part always sends"),
+    }
+}
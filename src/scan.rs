@@ -0,0 +1,362 @@
+//! scanf-style patterns for reading structured lines.
+//!
+//! A pattern like `"Card {id}: {winning} | {have}"` is literal text with named
+//! fields in braces; `{{` and `}}` stand for literal braces. A field takes
+//! everything up to the first place the literal after it matches, the last
+//! field takes the rest of the line, and field values are trimmed. A run of
+//! whitespace in the pattern matches any run of whitespace in the text, so
+//! `"Card {id}"` reads `Card   3` too.
+//!
+//! Errors carry the byte range they are about, so a checker can point at the
+//! exact spot on the line.
+
+use crate::check::{column_of, InputError};
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    parts: Vec<Part>,
+}
+
+/// What went wrong where: `offset` and `len` are in bytes of the scanned text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub offset: usize,
+    pub len: usize,
+    pub message: String,
+}
+
+impl ScanError {
+    pub fn new(offset: usize, len: usize, message: String) -> Self {
+        Self {
+            offset,
+            len,
+            message,
+        }
+    }
+
+    /// The same error for text that started `by` bytes into a longer one.
+    pub fn shifted(self, by: usize) -> Self {
+        Self {
+            offset: self.offset + by,
+            ..self
+        }
+    }
+
+    /// An `InputError` on line number `line`, whose text is `text`.
+    pub fn to_input_error(&self, line: usize, text: &str) -> InputError {
+        let start = self.offset.min(text.len());
+        let end = (self.offset + self.len).min(text.len());
+        InputError::new(
+            line,
+            column_of(text, start),
+            text[start..end].chars().count(),
+            self.message.clone(),
+        )
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at byte {})", self.message, self.offset)
+    }
+}
+
+impl Error for ScanError {}
+
+impl Pattern {
+    /// Panics when `pattern` has an unclosed brace, an empty or repeated field
+    /// name, or two fields with nothing between them to tell them apart.
+    pub fn new(pattern: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    if name.is_empty() || name.contains('{') {
+                        panic!("bad field '{{{}' in pattern '{}'", name, pattern);
+                    }
+                    if parts.contains(&Part::Field(name.clone())) {
+                        panic!("field '{}' appears twice in pattern '{}'", name, pattern);
+                    }
+                    if literal.is_empty() && matches!(parts.last(), Some(Part::Field(_))) {
+                        panic!("fields need text between them in pattern '{}'", pattern);
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(name));
+                }
+                '}' => panic!("unmatched '}}' in pattern '{}'", pattern),
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Self { parts }
+    }
+
+    /// The field names, in order.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Field(name) => Some(name.as_str()),
+            Part::Literal(_) => None,
+        })
+    }
+
+    pub fn matches<'p, 't>(&'p self, text: &'t str) -> Result<Fields<'p, 't>, ScanError> {
+        let mut found = Vec::new();
+        let mut pos = 0;
+        let mut field: Option<&str> = None;
+        for part in &self.parts {
+            match part {
+                Part::Field(name) => field = Some(name),
+                Part::Literal(literal) => {
+                    let end = match field.take() {
+                        // The field ends where the literal first matches
+                        Some(name) => {
+                            let at = (pos..=text.len())
+                                .filter(|&i| text.is_char_boundary(i))
+                                .find_map(|i| literal_at(text, i, literal).map(|end| (i, end)));
+                            let Some((start, end)) = at else {
+                                return Err(ScanError::new(
+                                    pos,
+                                    text.len() - pos,
+                                    format!("expected '{}' after {}", literal, name),
+                                ));
+                            };
+                            found.push(trimmed(name, text, pos, start));
+                            end
+                        }
+                        None => match literal_at(text, pos, literal) {
+                            Some(end) => end,
+                            None => {
+                                let shown = text[pos..].chars().take(literal.chars().count());
+                                return Err(ScanError::new(
+                                    pos,
+                                    shown.map(char::len_utf8).sum(),
+                                    format!("expected '{}'", literal),
+                                ));
+                            }
+                        },
+                    };
+                    pos = end;
+                }
+            }
+        }
+        match field {
+            Some(name) => found.push(trimmed(name, text, pos, text.len())),
+            None if pos < text.len() => {
+                return Err(ScanError::new(
+                    pos,
+                    text.len() - pos,
+                    format!("unexpected '{}' at the end", &text[pos..]),
+                ));
+            }
+            None => (),
+        }
+        Ok(Fields { text, found })
+    }
+}
+
+// The end of `literal` matched at byte `at` of `text`, whitespace matching any whitespace
+fn literal_at(text: &str, at: usize, literal: &str) -> Option<usize> {
+    let mut pos = at;
+    let mut expected = literal.chars().peekable();
+    while let Some(c) = expected.next() {
+        if c.is_whitespace() {
+            while expected.peek().is_some_and(|c| c.is_whitespace()) {
+                expected.next();
+            }
+            let rest = &text[pos..];
+            let skipped = rest.len() - rest.trim_start().len();
+            if skipped == 0 {
+                return None;
+            }
+            pos += skipped;
+        } else if text[pos..].starts_with(c) {
+            pos += c.len_utf8();
+        } else {
+            return None;
+        }
+    }
+    Some(pos)
+}
+
+// The field `name` at text[start..end] without surrounding whitespace
+fn trimmed<'p>(name: &'p str, text: &str, start: usize, end: usize) -> (&'p str, usize, usize) {
+    let value = &text[start..end];
+    let leading = value.len() - value.trim_start().len();
+    (name, start + leading, start + leading + value.trim().len())
+}
+
+/// The fields of one match, by name. Asking for a name the pattern does not
+/// have is a bug in the caller and panics.
+#[derive(Debug, Clone)]
+pub struct Fields<'p, 't> {
+    text: &'t str,
+    found: Vec<(&'p str, usize, usize)>,
+}
+
+impl<'t> Fields<'_, 't> {
+    fn span(&self, name: &str) -> (usize, usize) {
+        match self.found.iter().find(|(n, _, _)| *n == name) {
+            Some(&(_, start, end)) => (start, end),
+            None => panic!("the pattern has no field '{}'", name),
+        }
+    }
+
+    pub fn str(&self, name: &str) -> &'t str {
+        let (start, end) = self.span(name);
+        &self.text[start..end]
+    }
+
+    /// Byte offset of the field in the scanned text.
+    pub fn offset(&self, name: &str) -> usize {
+        self.span(name).0
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, ScanError> {
+        parse(name, self.str(name), self.offset(name))
+    }
+
+    /// The field as whitespace-separated values.
+    pub fn list<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ScanError> {
+        self.split_whitespace(name)
+            .map(|(offset, value)| parse(name, value, offset))
+            .collect()
+    }
+
+    /// The field split on `separator`, see `split`.
+    pub fn split<'a>(
+        &'a self,
+        name: &str,
+        separator: &'a str,
+    ) -> impl Iterator<Item = (usize, &'t str)> + 'a {
+        let base = self.offset(name);
+        split(self.str(name), separator).map(move |(offset, piece)| (base + offset, piece))
+    }
+
+    fn split_whitespace(&self, name: &str) -> impl Iterator<Item = (usize, &'t str)> {
+        let field = self.str(name);
+        let base = self.offset(name);
+        field.split_whitespace().map(move |value| {
+            (
+                base + value.as_ptr() as usize - field.as_ptr() as usize,
+                value,
+            )
+        })
+    }
+}
+
+/// `text` split on `separator`, each piece trimmed and with its byte offset in `text`.
+pub fn split<'t, 's>(
+    text: &'t str,
+    separator: &'s str,
+) -> impl Iterator<Item = (usize, &'t str)> + use<'t, 's> {
+    text.split(separator).map(move |piece| {
+        let offset = piece.as_ptr() as usize - text.as_ptr() as usize;
+        let leading = piece.len() - piece.trim_start().len();
+        (offset + leading, piece.trim())
+    })
+}
+
+fn parse<T: FromStr>(name: &str, value: &str, offset: usize) -> Result<T, ScanError> {
+    value.parse().map_err(|_| {
+        let wanted = type_name::<T>().rsplit("::").next().unwrap_or("value");
+        let message = if value.is_empty() {
+            format!("{} is missing", name)
+        } else {
+            format!("expected {} for {}, found '{}'", wanted, name, value)
+        };
+        ScanError::new(offset, value.len(), message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_typed_fields() {
+        let card = Pattern::new("Card {id}: {winning} | {have}");
+        let fields = card.matches("Card   3: 41 48 | 83  86 6").unwrap();
+        assert_eq!(fields.get::<u32>("id"), Ok(3));
+        assert_eq!(fields.list::<u8>("winning"), Ok(vec![41, 48]));
+        assert_eq!(fields.list::<u8>("have"), Ok(vec![83, 86, 6]));
+        assert_eq!(fields.offset("have"), 18);
+        assert_eq!(card.fields().collect::<Vec<_>>(), ["id", "winning", "have"]);
+
+        let node = Pattern::new("{key} = ({left}, {right})");
+        let fields = node.matches("AAA = (BBB, CCC)").unwrap();
+        assert_eq!(
+            [fields.str("key"), fields.str("left"), fields.str("right")],
+            ["AAA", "BBB", "CCC"]
+        );
+        let braces = Pattern::new("{{{name}}}");
+        assert_eq!(braces.matches("{x}").unwrap().str("name"), "x");
+    }
+
+    #[test]
+    fn splits_fields_with_offsets() {
+        let game = Pattern::new("Game {id}: {rounds}");
+        let fields = game.matches("Game 1: 3 blue; 4 red, 1 green").unwrap();
+        let rounds: Vec<(usize, &str)> = fields.split("rounds", ";").collect();
+        assert_eq!(rounds, vec![(8, "3 blue"), (16, "4 red, 1 green")]);
+        let draws: Vec<(usize, &str)> = split(rounds[1].1, ",").collect();
+        assert_eq!(draws, vec![(0, "4 red"), (7, "1 green")]);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let card = Pattern::new("Card {id}: {winning} | {have}");
+        let error = card.matches("Card 1: 41 48 83 86").unwrap_err();
+        assert_eq!(error.message, "expected ' | ' after winning");
+        assert_eq!((error.offset, error.len), (8, 11));
+
+        let error = card.matches("Crd 1: 2 | 3").unwrap_err();
+        assert_eq!(error.message, "expected 'Card '");
+
+        let fields = card.matches("Card x1: 2 | 3 y").unwrap();
+        let error = fields.get::<u32>("id").unwrap_err();
+        assert_eq!(error.message, "expected u32 for id, found 'x1'");
+        assert_eq!((error.offset, error.len), (5, 2));
+        let error = fields.list::<u32>("have").unwrap_err();
+        assert_eq!((error.offset, error.len), (15, 1));
+        let line = "Card x1: 2 | 3 y";
+        let input_error = error.shifted(0).to_input_error(4, line);
+        assert_eq!((input_error.line, input_error.column), (4, 15));
+
+        let error = Pattern::new("{a} end").matches("1 end here").unwrap_err();
+        assert_eq!(error.message, "unexpected ' here' at the end");
+        let pair = Pattern::new("{a}:{b}");
+        let fields = pair.matches(":2").unwrap();
+        assert_eq!(fields.get::<u8>("a").unwrap_err().message, "a is missing");
+    }
+
+    #[test]
+    #[should_panic(expected = "fields need text between them")]
+    fn adjacent_fields_are_ambiguous() {
+        Pattern::new("{a}{b}");
+    }
+}
@@ -0,0 +1,462 @@
+//! Local HTTP API over the solvers, for tools that would rather not link the crate.
+//!
+//! ```text
+//! GET  /days          every day, its title and whether its input is stored
+//! GET  /days/N/P      part P of day N on the stored input
+//! POST /days/N/P      part P of day N on the request body
+//! ```
+//!
+//! Every response is JSON. A run answers with `PartRun::to_json`, anything
+//! else that goes wrong with `{"error": "..."}` and a fitting status; posted
+//! inputs the strict checker rejects also list the problems it found. The
+//! server speaks just enough HTTP/1.1 for curl and the usual client libraries,
+//! one request per connection, each on a thread of its own. Past
+//! `MAX_CONNECTIONS` at once, new connections are turned away with a 503, and
+//! a client that stops sending for `READ_TIMEOUT` gets a 400.
+
+use crate::aoc::AocError;
+use crate::cache::ResultCache;
+use crate::check::check_lines;
+use crate::config;
+use crate::explain::json_string;
+use crate::fuzz::panic_message;
+use crate::input;
+use crate::runner::{run_part, run_within, PartRun};
+use crate::{input_path_for_day, solver_for_day, title_for_day, NUM_DAYS};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+/// Larger posted inputs are refused, the biggest stored one is about 20 KiB.
+pub const MAX_BODY: usize = 16 << 20;
+const MAX_HEADER_LINE: usize = 8 << 10;
+
+pub const MAX_CONNECTIONS: usize = 64;
+pub const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\": {}}}", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            self.body
+        )?;
+        out.flush()
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    cache: Option<Arc<ResultCache>>,
+    connections: Arc<AtomicUsize>,
+    max_connections: usize,
+    read_timeout: Duration,
+}
+
+// Counts a connection as open until it is dropped
+struct Connection(Arc<AtomicUsize>);
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Server {
+    /// Listens on `addr`, answering stored and posted inputs from `cache` when
+    /// it already holds them.
+    pub fn bind(addr: impl ToSocketAddrs, cache: Option<ResultCache>) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            cache: cache.map(Arc::new),
+            connections: Arc::new(AtomicUsize::new(0)),
+            max_connections: MAX_CONNECTIONS,
+            read_timeout: READ_TIMEOUT,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves requests until the process exits.
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let mut stream = stream?;
+            let connection = Connection(self.connections.clone());
+            if self.connections.fetch_add(1, Ordering::Relaxed) >= self.max_connections {
+                let busy = Response::error(503, "too many connections, try again later");
+                let _ = busy.write_to(&mut stream);
+                continue;
+            }
+            let cache = self.cache.clone();
+            let read_timeout = self.read_timeout;
+            thread::spawn(move || {
+                let _connection = connection;
+                // A client hanging up early is its own problem
+                let _ = serve_connection(stream, cache.as_deref(), read_timeout);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn serve_connection(
+    mut stream: TcpStream,
+    cache: Option<&ResultCache>,
+    read_timeout: Duration,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(read_timeout))?;
+    let response = match read_request(&mut BufReader::new(&stream)) {
+        Ok((method, path, body)) => respond(&method, &path, &body, cache),
+        Err(response) => response,
+    };
+    response.write_to(&mut stream)
+}
+
+// The method, the path without its query and the body, or the response
+// refusing the request
+fn read_request(reader: &mut impl BufRead) -> Result<(String, String, Vec<u8>), Response> {
+    let bad_request = |message: &str| Response::error(400, message);
+    let mut read_line = || {
+        let mut line = String::new();
+        let mut limited = reader.by_ref().take(MAX_HEADER_LINE as u64);
+        match limited.read_line(&mut line) {
+            Ok(_) if line.ends_with('\n') => Ok(line.trim_end().to_string()),
+            _ => Err(bad_request("malformed request")),
+        }
+    };
+    let request_line = read_line()?;
+    let mut words = request_line.split(' ');
+    let (Some(method), Some(target), Some(_version)) = (words.next(), words.next(), words.next())
+    else {
+        return Err(bad_request("malformed request line"));
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut length = 0;
+    loop {
+        let header = read_line()?;
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request("malformed header"));
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .trim()
+                .parse()
+                .map_err(|_| bad_request("malformed Content-Length"))?;
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Response::error(413, "the input is too large"));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad_request("the body is shorter than its Content-Length"))?;
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    Ok((method, path, body))
+}
+
+/// Answers one request, `body` being the posted input if any.
+pub fn respond(method: &str, path: &str, body: &[u8], cache: Option<&ResultCache>) -> Response {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        ("GET", ["days"]) => Response::ok(list_days()),
+        ("GET", ["days", day, part]) => run(day, part, None, cache),
+        ("POST", ["days", day, part]) => run(day, part, Some(body), cache),
+        (_, ["days"]) | (_, ["days", _, _]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, &format!("no such endpoint: {}", path)),
+    }
+}
+
+fn list_days() -> String {
+    let days: Vec<String> = (1..=NUM_DAYS)
+        .map(|day| {
+            format!(
+                "{{\"day\": {}, \"title\": {}, \"input\": {}}}",
+                day,
+                json_string(title_for_day(day).unwrap_or_default()),
+                Path::new(&input_path_for_day(day)).is_file()
+            )
+        })
+        .collect();
+    format!("[{}]", days.join(", "))
+}
+
+// Gives every posted input a file of its own, solvers read their input from a path
+static POSTED: AtomicU64 = AtomicU64::new(0);
+
+// A posted input's file, removed once the last part reading it is done with it
+struct PostedInput {
+    path: PathBuf,
+}
+
+impl PostedInput {
+    fn store(input: &[u8]) -> io::Result<Self> {
+        let path = env::temp_dir().join(format!(
+            "aoc-serve-{}-{}.input",
+            process::id(),
+            POSTED.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, input)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PostedInput {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn run(day: &str, part: &str, input: Option<&[u8]>, cache: Option<&ResultCache>) -> Response {
+    let day = day
+        .parse::<u32>()
+        .ok()
+        .filter(|d| (1..=NUM_DAYS).contains(d));
+    let Some(day) = day else {
+        return Response::error(404, &format!("there are days 1 to {}", NUM_DAYS));
+    };
+    let Some(part) = part.parse::<u32>().ok().filter(|p| *p == 1 || *p == 2) else {
+        return Response::error(404, "there are parts 1 and 2");
+    };
+    let Some(input) = input else {
+        let path_to_input = input_path_for_day(day);
+        if !Path::new(&path_to_input).is_file() {
+            return Response::error(404, &format!("day {} has no stored input", day));
+        }
+        return run_on(day, part, &path_to_input, None, cache);
+    };
+
+    let Ok(text) = std::str::from_utf8(input) else {
+        return Response::error(400, "the input is not UTF-8");
+    };
+    if let Some(problems) = rejected(day, text) {
+        return problems;
+    }
+    let posted = match PostedInput::store(input) {
+        Ok(posted) => posted,
+        Err(e) => return Response::error(500, &format!("could not store the input: {}", e)),
+    };
+    let path_to_input = posted.path.to_string_lossy().to_string();
+    run_on(day, part, &path_to_input, Some(posted), cache)
+}
+
+// The solvers panic on malformed inputs, the checker explains what is wrong instead
+fn rejected(day: u32, text: &str) -> Option<Response> {
+    let mut errors = check_lines(day, &input::lines(text))?;
+    if errors.is_empty() {
+        return None;
+    }
+    errors.sort_by_key(|e| (e.line, e.column));
+    let problems: Vec<String> = errors
+        .iter()
+        .map(|e| {
+            format!(
+                "{{\"line\": {}, \"column\": {}, \"message\": {}}}",
+                e.line + 1,
+                e.column + 1,
+                json_string(&e.message)
+            )
+        })
+        .collect();
+    Some(Response {
+        status: 422,
+        body: format!(
+            "{{\"error\": \"the input does not check out\", \"problems\": [{}]}}",
+            problems.join(", ")
+        ),
+    })
+}
+
+fn run_on(
+    day: u32,
+    part: u32,
+    path_to_input: &str,
+    posted: Option<PostedInput>,
+    cache: Option<&ResultCache>,
+) -> Response {
+    let run = panic::catch_unwind(AssertUnwindSafe(|| -> Result<PartRun, AocError> {
+        if let Some(timeout) = config::current().timeout {
+            let path_to_input = path_to_input.to_string();
+            let cache = cache.cloned();
+            // A part that times out keeps running, so it holds on to the posted input
+            return run_within(day, part, timeout, move || {
+                let _posted = posted;
+                solve_on(day, part, &path_to_input, cache.as_ref())
+            });
+        }
+        solve_on(day, part, path_to_input, cache)
+    }));
+    match run {
+        Ok(Ok(run)) => Response::ok(run.to_json()),
+        Ok(Err(e @ AocError::Timeout { .. })) => Response::error(504, &e.to_string()),
+        Ok(Err(e)) => Response::error(422, &e.to_string()),
+        Err(payload) => Response::error(
+            500,
+            &format!(
+                "day{} part{} panicked: {}",
+                day,
+                part,
+                panic_message(payload.as_ref())
+            ),
+        ),
+    }
+}
+
+fn solve_on(
+    day: u32,
+    part: u32,
+    path_to_input: &str,
+    cache: Option<&ResultCache>,
+) -> Result<PartRun, AocError> {
+    let Some(solver) = solver_for_day(day, path_to_input) else {
+        panic!("there is no day {}!", day);
+    };
+    run_part(solver.as_ref(), day, part, path_to_input, cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric;
+    use crate::runner::solve_part;
+
+    // Sends one raw request to a fresh server and returns the status and body
+    fn request(method: &str, path: &str, body: &str) -> (u16, String) {
+        let server = Server::bind("127.0.0.1:0", None).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    #[test]
+    fn lists_days_and_runs_stored_inputs() {
+        let (status, body) = request("GET", "/days", "");
+        assert_eq!(status, 200);
+        assert!(body.starts_with("[{\"day\": 1, \"title\": \"Trebuchet?!\", \"input\": true}, "));
+
+        let (status, body) = request("GET", "/days/7/1?verbose", "");
+        assert_eq!(status, 200);
+        assert!(body
+            .starts_with("{\"day\": 7, \"part\": 1, \"answer\": 251136060, \"cached\": false, "));
+    }
+
+    #[test]
+    fn runs_posted_inputs() {
+        let input = fs::read_to_string("./inputs/day7_test.input").unwrap();
+        let solver = solver_for_day(7, "./inputs/day7_test.input").unwrap();
+        let expected = solve_part(solver.as_ref(), 2);
+        let (status, body) = request("POST", "/days/7/2", &input);
+        assert_eq!(status, 200);
+        assert!(body.contains(&format!("\"answer\": {}, ", expected)));
+
+        let (status, body) = request("POST", "/days/7/1", "32T3K 765\nKK6X7 28\n");
+        assert_eq!(status, 422);
+        assert_eq!(
+            body,
+            "{\"error\": \"the input does not check out\", \"problems\": [{\"line\": 2, \"column\": 4, \"message\": \"unknown card 'X'\"}]}"
+        );
+    }
+
+    #[test]
+    fn reports_bad_requests_as_json() {
+        assert_eq!(
+            request("GET", "/days/11/1", ""),
+            (404, "{\"error\": \"there are days 1 to 10\"}".to_string())
+        );
+        assert_eq!(request("GET", "/days/1/3", "").0, 404);
+        assert_eq!(request("DELETE", "/days", "").0, 405);
+        assert_eq!(request("GET", "/nope", "").0, 404);
+
+        let _checked = numeric::checked_scope(true);
+        assert_eq!(
+            request("POST", "/days/9/1", "7 1517886983\n"),
+            (
+                422,
+                "{\"error\": \"day9 part1: i32: 1517886983 + 1517886976 overflows\"}".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn turns_away_connections_past_the_limit_and_clients_that_stall() {
+        let mut server = Server::bind("127.0.0.1:0", None).unwrap();
+        server.max_connections = 1;
+        server.read_timeout = Duration::from_millis(200);
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        let read_all = |mut stream: TcpStream| {
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        // The first client never sends a thing and holds the only connection
+        let stalled = TcpStream::connect(addr).unwrap();
+        let turned_away = read_all(TcpStream::connect(addr).unwrap());
+        assert!(turned_away.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(read_all(stalled).starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+}
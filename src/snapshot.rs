@@ -0,0 +1,227 @@
+//! Snapshot tests of the models the solvers build from the example inputs.
+//!
+//! Every example input, `inputs/dayN_test*.input` and the like, of a day whose
+//! solver has an `Aoc::snapshot` is rendered and compared with the text stored
+//! in `tests/snapshots/<input>.snap`. A rendering that is new or differs is
+//! written next to it as `<input>.snap.new`, where `aoc snapshots review`
+//! shows it as a diff to accept or reject; `aoc snapshots accept` takes all of
+//! them. `tests/snapshots.rs` fails while any snapshot doesn't match.
+
+use crate::{config, solver_for_day, NUM_DAYS};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_SNAPSHOT_DIR: &str = "./tests/snapshots";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Unchanged,
+    New,
+    Changed { expected: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The input's file name without `.input`, e.g. `day9_test2`
+    pub name: String,
+    pub actual: String,
+    pub status: Status,
+}
+
+/// The example inputs of `day` in `inputs`, by name.
+fn example_inputs(inputs: &Path, day: u32) -> Vec<(String, PathBuf)> {
+    let prefix = format!("day{}_", day);
+    let Ok(entries) = fs::read_dir(inputs) else {
+        return Vec::new();
+    };
+    let mut found: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter_map(|path| {
+            let name = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".input")?
+                .to_string();
+            (name.starts_with(&prefix) && name.contains("test")).then_some((name, path))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Renders every snapshot, by name.
+pub fn render_all() -> Vec<(String, String)> {
+    let inputs = config::current().inputs.clone();
+    let mut rendered = Vec::new();
+    for day in 1..=NUM_DAYS {
+        for (name, path) in example_inputs(&inputs, day) {
+            let snapshot = solver_for_day(day, &path.to_string_lossy()).and_then(|s| s.snapshot());
+            if let Some(snapshot) = snapshot {
+                rendered.push((name, snapshot));
+            }
+        }
+    }
+    rendered
+}
+
+fn stored_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.snap", name))
+}
+
+fn pending_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.snap.new", name))
+}
+
+/// Compares every snapshot with the one stored in `dir`, leaving the ones that
+/// don't match there for review and clearing reviews that are out of date.
+pub fn check(dir: &Path) -> io::Result<Vec<Outcome>> {
+    let mut outcomes = Vec::new();
+    for (name, actual) in render_all() {
+        let status = match fs::read_to_string(stored_path(dir, &name)) {
+            Ok(expected) if expected == actual => Status::Unchanged,
+            Ok(expected) => Status::Changed { expected },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Status::New,
+            Err(e) => return Err(e),
+        };
+        let pending = pending_path(dir, &name);
+        if status == Status::Unchanged {
+            if pending.exists() {
+                fs::remove_file(pending)?;
+            }
+        } else {
+            fs::create_dir_all(dir)?;
+            fs::write(pending, &actual)?;
+        }
+        outcomes.push(Outcome {
+            name,
+            actual,
+            status,
+        });
+    }
+    Ok(outcomes)
+}
+
+/// The names of the snapshots waiting for review in `dir`.
+pub fn pending(dir: &Path) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| {
+            Some(
+                e.file_name()
+                    .to_str()?
+                    .strip_suffix(".snap.new")?
+                    .to_string(),
+            )
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// The stored snapshot `name` and the one waiting for review, if any.
+pub fn review(dir: &Path, name: &str) -> io::Result<(Option<String>, String)> {
+    let stored = fs::read_to_string(stored_path(dir, name)).ok();
+    Ok((stored, fs::read_to_string(pending_path(dir, name))?))
+}
+
+/// Makes the snapshot waiting for review the stored one.
+pub fn accept(dir: &Path, name: &str) -> io::Result<()> {
+    fs::rename(pending_path(dir, name), stored_path(dir, name))
+}
+
+pub fn reject(dir: &Path, name: &str) -> io::Result<()> {
+    fs::remove_file(pending_path(dir, name))
+}
+
+/// A line diff of `expected` and `actual`, `-` marking lines only in the first
+/// and `+` lines only in the second.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    // lcs[i][j] is the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push_str(&format!("  {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", old[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", new[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_lines() {
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+            "  a\n- b\n+ x\n  c\n+ d\n"
+        );
+        assert_eq!(diff("", "a"), "+ a\n");
+        assert_eq!(diff("same\n", "same\n"), "  same\n");
+    }
+
+    #[test]
+    fn reviews_new_and_changed_snapshots() {
+        let dir = std::env::temp_dir().join(format!("aoc-snapshot-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let outcomes = check(&dir).unwrap();
+        assert!(outcomes.iter().any(|o| o.name == "day7_test"));
+        assert!(outcomes.iter().all(|o| o.status == Status::New));
+        let names = pending(&dir).unwrap();
+        assert_eq!(names.len(), outcomes.len());
+        for name in &names {
+            accept(&dir, name).unwrap();
+        }
+        assert!(check(&dir)
+            .unwrap()
+            .iter()
+            .all(|o| o.status == Status::Unchanged));
+
+        fs::write(stored_path(&dir, "day7_test"), "stale\n").unwrap();
+        let outcomes = check(&dir).unwrap();
+        let changed = outcomes.iter().find(|o| o.name == "day7_test").unwrap();
+        assert_eq!(
+            changed.status,
+            Status::Changed {
+                expected: "stale\n".to_string()
+            }
+        );
+        assert_eq!(pending(&dir).unwrap(), ["day7_test"]);
+        let (stored, actual) = review(&dir, "day7_test").unwrap();
+        assert_eq!(
+            (stored.as_deref(), actual.as_str()),
+            (Some("stale\n"), changed.actual.as_str())
+        );
+        reject(&dir, "day7_test").unwrap();
+        assert!(pending(&dir).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
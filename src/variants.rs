@@ -0,0 +1,177 @@
+//! Alternative implementations of a part, registered by name.
+//!
+//! A day that keeps more than one approach around returns them from
+//! `Aoc::variants`, the one `part1`/`part2` use first. Any of them can be run
+//! by name, or all of them side by side to see whether they agree and how
+//! their speed compares. Days without variants have a single "default" one.
+
+use crate::aoc::{Aoc, AocRes};
+use crate::progress::with_progress;
+use crate::runner::solve_part;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+pub struct Variant<'a> {
+    pub name: &'static str,
+    /// Too slow for full inputs, only run when asked for by name
+    pub slow: bool,
+    pub solve: Box<dyn Fn() -> AocRes + 'a>,
+}
+
+impl<'a> Variant<'a> {
+    pub fn new(name: &'static str, solve: impl Fn() -> AocRes + 'a) -> Self {
+        Self {
+            name,
+            slow: false,
+            solve: Box::new(solve),
+        }
+    }
+
+    pub fn slow(mut self) -> Self {
+        self.slow = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantRun {
+    pub name: &'static str,
+    /// `None` for a slow variant that was skipped
+    pub answer: Option<AocRes>,
+    pub duration: Duration,
+}
+
+/// The variants of `part`, or the part itself as "default" when there are none.
+pub fn variants_of(solver: &dyn Aoc, part: u32) -> Vec<Variant<'_>> {
+    let variants = solver.variants(part);
+    if variants.is_empty() {
+        vec![Variant::new("default", move || solve_part(solver, part))]
+    } else {
+        variants
+    }
+}
+
+/// Runs the variant called `name`, slow or not.
+pub fn run_variant(solver: &dyn Aoc, day: u32, part: u32, name: &str) -> Option<VariantRun> {
+    let variant = variants_of(solver, part)
+        .into_iter()
+        .find(|v| v.name == name)?;
+    Some(run(&variant, day, part))
+}
+
+/// Runs every variant of `part` in order, skipping slow ones unless `include_slow`.
+pub fn run_all(solver: &dyn Aoc, day: u32, part: u32, include_slow: bool) -> Vec<VariantRun> {
+    variants_of(solver, part)
+        .iter()
+        .map(|variant| {
+            if variant.slow && !include_slow {
+                VariantRun {
+                    name: variant.name,
+                    answer: None,
+                    duration: Duration::ZERO,
+                }
+            } else {
+                run(variant, day, part)
+            }
+        })
+        .collect()
+}
+
+fn run(variant: &Variant, day: u32, part: u32) -> VariantRun {
+    let label = format!("day{} part{} {}", day, part, variant.name);
+    let start = Instant::now();
+    let answer = with_progress(&label, || (variant.solve)());
+    VariantRun {
+        name: variant.name,
+        answer: Some(answer),
+        duration: start.elapsed(),
+    }
+}
+
+/// Whether every variant that ran gave the same answer.
+pub fn agree(runs: &[VariantRun]) -> bool {
+    let mut answers = runs.iter().filter_map(|r| r.answer);
+    let Some(first) = answers.next() else {
+        return true;
+    };
+    answers.all(|a| a == first)
+}
+
+/// One line per variant with its answer, time and how much slower than the
+/// fastest one it was.
+pub fn report(runs: &[VariantRun]) -> String {
+    let fastest = runs
+        .iter()
+        .filter(|r| r.answer.is_some())
+        .map(|r| r.duration)
+        .min()
+        .unwrap_or_default()
+        .max(Duration::from_nanos(1));
+    let first = runs.iter().find_map(|r| r.answer);
+    let width = runs.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for run in runs {
+        let Some(answer) = run.answer else {
+            writeln!(
+                out,
+                "  {:<width$}  skipped (slow, run it by name)",
+                run.name
+            )
+            .unwrap();
+            continue;
+        };
+        let relative = run.duration.as_secs_f64() / fastest.as_secs_f64();
+        let verdict = if Some(answer) == first {
+            ""
+        } else {
+            "  DIFFERS"
+        };
+        writeln!(
+            out,
+            "  {:<width$}  {:>16}  {:>10.3?}  {:>7.2}x{}",
+            run.name,
+            answer.to_string(),
+            run.duration,
+            relative,
+            verdict
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(name: &'static str, answer: Option<i32>, millis: u64) -> VariantRun {
+        VariantRun {
+            name,
+            answer: answer.map(AocRes::Int32),
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn skipped_variants_do_not_count() {
+        let runs = [run("fast", Some(4), 10), run("slow", None, 0)];
+        assert!(agree(&runs));
+        assert!(!agree(&[run("a", Some(4), 1), run("b", Some(5), 1)]));
+    }
+
+    #[test]
+    fn reports_speed_relative_to_the_fastest() {
+        let runs = [
+            run("first", Some(4), 30),
+            run("second", Some(4), 10),
+            run("third", Some(5), 20),
+            run("fourth", None, 0),
+        ];
+        let report = report(&runs);
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[0].ends_with("3.00x"), "{}", lines[0]);
+        assert!(lines[1].ends_with("1.00x"), "{}", lines[1]);
+        assert!(lines[2].ends_with("2.00x  DIFFERS"), "{}", lines[2]);
+        assert!(lines[3].ends_with("skipped (slow, run it by name)"));
+    }
+}
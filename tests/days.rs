@@ -0,0 +1,205 @@
+use aoc::day7::HandTypes;
+use aoc::{solver_for_day, Aoc, AocRes, Day6, Day7, NUM_DAYS};
+
+#[test]
+fn every_day_has_a_solver() {
+    for day in 1..=NUM_DAYS {
+        assert!(solver_for_day(day, "./inputs/day1.input").is_some());
+    }
+    assert!(solver_for_day(NUM_DAYS + 1, "./inputs/day1.input").is_none());
+}
+
+#[test]
+fn day5_works_on_sample_input() {
+    let day5 = solver_for_day(5, "./inputs/day5_test2.input").unwrap();
+    assert_eq!(day5.part1(), AocRes::UInt64(35));
+    assert_eq!(day5.part2(), AocRes::UInt64(46));
+}
+
+#[test]
+fn day5_map_ranges_exclude_their_end() {
+    // 98 and 99 move to 50 and 51, 100 is just past the range and stays put
+    let path = std::env::temp_dir().join(format!("aoc-day5-{}.input", std::process::id()));
+    std::fs::write(&path, "seeds: 99 100\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+    let day5 = solver_for_day(5, path.to_str().unwrap()).unwrap();
+    assert_eq!(day5.part1(), AocRes::UInt64(51));
+    assert_eq!(day5.part2(), AocRes::UInt64(51));
+    std::fs::write(&path, "seeds: 100 100\n\nseed-to-soil map:\n50 98 2\n").unwrap();
+    let day5 = solver_for_day(5, path.to_str().unwrap()).unwrap();
+    assert_eq!(day5.part1(), AocRes::UInt64(100));
+    assert_eq!(day5.part2(), AocRes::UInt64(100));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn day6_works_on_sample_input() {
+    let day6 = Day6::new("./inputs/day6_test.input");
+    assert_eq!(day6.part1(), AocRes::Int32(288));
+    assert_eq!(day6.part2(), AocRes::Int32(71503));
+}
+
+#[test]
+fn day7_works_on_sample_input() {
+    let day7 = solver_for_day(7, "./inputs/day7_test.input").unwrap();
+    assert_eq!(day7.part1(), AocRes::Int32(9814));
+    assert_eq!(day7.part2(), AocRes::Int32(9343));
+}
+
+#[test]
+fn day7_hand_classifier_is_public() {
+    let (hand_type, sorted) = Day7::categorize_type_part1("KTJJT");
+    assert_eq!(hand_type, HandTypes::TwoPair(11, 10, 13));
+    assert_eq!(sorted, "TTJJK");
+    let (hand_type, _) = Day7::categorize_type_part2("KTJJT");
+    assert!(matches!(hand_type, HandTypes::FourOfAKind(_, _)));
+}
+
+#[test]
+fn explanations_add_up_to_the_answer() {
+    use aoc::explain::Cell;
+    let day7 = solver_for_day(7, "./inputs/day7_test.input").unwrap();
+    for part in 1..=2 {
+        let explanation = day7.explain(part).unwrap();
+        assert_eq!(explanation.columns.last(), Some(&"rank x bid"));
+        let total: i64 = explanation
+            .rows
+            .iter()
+            .map(|row| match row.last() {
+                Some(Cell::Int(n)) => *n,
+                _ => panic!("rank x bid is not a number"),
+            })
+            .sum();
+        assert_eq!(AocRes::Int32(total as i32), explanation.answer);
+    }
+    assert!(solver_for_day(5, "./inputs/day5_test.input").unwrap().explain(1).is_none());
+}
+
+#[test]
+fn grid_days_color_their_cells() {
+    let day10 = solver_for_day(10, "./inputs/day10_test2.input").unwrap();
+    let image = day10.image().unwrap();
+    let count = |category: &str| {
+        let index = image.legend.iter().position(|(name, _)| *name == category).unwrap();
+        image.cells.iter().filter(|&&c| c == index).count()
+    };
+    assert_eq!(count("inside"), 4);
+    assert_eq!(count("S"), 1);
+    assert_eq!(count("main loop"), 45);
+
+    let day3 = solver_for_day(3, "./inputs/day3.input").unwrap();
+    let image = day3.image().unwrap();
+    assert_eq!((image.width, image.height), (140, 140));
+    assert!(solver_for_day(7, "./inputs/day7.input").unwrap().image().is_none());
+}
+
+#[test]
+fn long_parts_report_progress() {
+    use aoc::progress::track;
+    let day6 = Day6::new("./inputs/day6_test.input");
+    let (answer, snapshot) = track(|| day6.part2());
+    assert_eq!(answer, AocRes::Int32(71503));
+    assert_eq!(snapshot.total, Some(71530));
+
+    let day8 = solver_for_day(8, "./inputs/day8_test.input").unwrap();
+    let (_, snapshot) = track(|| day8.part1());
+    assert_eq!((snapshot.current, snapshot.total), (6, None));
+}
+
+#[test]
+fn variants_agree_on_sample_inputs() {
+    use aoc::variants::{agree, run_all, run_variant};
+    let day6 = solver_for_day(6, "./inputs/day6_test.input").unwrap();
+    let runs = run_all(day6.as_ref(), 6, 2, false);
+    assert_eq!(runs.len(), 2);
+    assert!(agree(&runs));
+    assert_eq!(runs[1].answer, Some(AocRes::Int32(71503)));
+
+    let day8 = solver_for_day(8, "./inputs/day8_p2_test.input").unwrap();
+    let runs = run_all(day8.as_ref(), 8, 2, false);
+    assert_eq!(runs[1].answer, None);
+    let lockstep = run_variant(day8.as_ref(), 8, 2, "lockstep").unwrap();
+    assert_eq!(lockstep.answer, Some(AocRes::UInt64(6)));
+    assert!(run_variant(day8.as_ref(), 8, 2, "default").is_none());
+
+    let day7 = solver_for_day(7, "./inputs/day7_test.input").unwrap();
+    for part in 1..=2 {
+        let runs = run_all(day7.as_ref(), 7, part, false);
+        assert_eq!(runs.len(), 2);
+        assert!(agree(&runs), "day 7 part {}", part);
+    }
+
+    // Days without variants still have the part itself
+    let day9 = solver_for_day(9, "./inputs/day9.input").unwrap();
+    let runs = run_all(day9.as_ref(), 9, 1, false);
+    assert_eq!(runs[0].name, "default");
+}
+
+#[test]
+fn crlf_inputs_give_the_same_answers() {
+    let dir = std::env::temp_dir().join(format!("aoc-crlf-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for (day, sample) in [(5, "day5_test"), (6, "day6_test"), (7, "day7_test"), (8, "day8_test")] {
+        let original = format!("./inputs/{}.input", sample);
+        let text = std::fs::read_to_string(&original).unwrap();
+        let crlf = dir.join(format!("{}.input", sample));
+        std::fs::write(&crlf, text.replace('\n', "\r\n") + "\r\n").unwrap();
+        let expected = solver_for_day(day, &original).unwrap();
+        let actual = solver_for_day(day, crlf.to_str().unwrap()).unwrap();
+        assert_eq!(actual.part1(), expected.part1(), "day {}", day);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn saved_regressions_pass() {
+    // Saved by `aoc diff` as dayD_partP_sizeZ_seedS.input with the reference answer next to it
+    let mut checked = 0;
+    for entry in std::fs::read_dir("./inputs/regressions").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|e| e != "input") {
+            continue;
+        }
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let fields: Vec<&str> = name.split('_').collect();
+        let day = fields[0].trim_start_matches("day").parse::<u32>().unwrap();
+        let part = fields[1].trim_start_matches("part").parse::<u32>().unwrap();
+        let answer = std::fs::read_to_string(path.with_extension("answer")).unwrap();
+        let expected = aoc::cache::decode(answer.trim()).unwrap();
+        let solver = solver_for_day(day, path.to_str().unwrap()).unwrap();
+        assert_eq!(aoc::runner::solve_part(solver.as_ref(), part), expected, "{}", name);
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn checked_arithmetic_reports_overflow_as_an_error() {
+    use aoc::runner::try_solve_part;
+    use aoc::AocError;
+    let _checked = aoc::numeric::checked_scope(true);
+    let day9 = solver_for_day(9, "./tests/fuzz_regressions/day9-da96942ca67adbe0.input").unwrap();
+    let Err(AocError::Overflow { day, part, operation }) = try_solve_part(day9.as_ref(), 9, 1)
+    else {
+        panic!("expected an overflow");
+    };
+    assert_eq!((day, part), (9, 1));
+    assert_eq!(operation, "i32: 1517886983 + 1517886976");
+    let day6 = solver_for_day(6, "./inputs/day6_test.input").unwrap();
+    assert_eq!(try_solve_part(day6.as_ref(), 6, 2).unwrap(), AocRes::Int32(71503));
+}
+
+#[test]
+fn worker_panics_are_reported_as_errors() {
+    use aoc::runner::try_solve_part;
+    use aoc::AocError;
+    // The seed maps past the largest u64
+    let path = std::env::temp_dir().join(format!("aoc-day5-{}.input", std::process::id()));
+    std::fs::write(&path, "seeds: 10\n\nseed-to-soil map:\n18446744073709551615 5 10\n").unwrap();
+    let day5 = solver_for_day(5, path.to_str().unwrap()).unwrap();
+    let Err(AocError::Worker { day, part, message }) = try_solve_part(day5.as_ref(), 5, 1) else {
+        panic!("expected a worker panic");
+    };
+    assert_eq!((day, part), (5, 1));
+    assert_eq!(message, "Mapping 10 to 18446744073709551615 overflows!");
+    let _ = std::fs::remove_file(&path);
+}
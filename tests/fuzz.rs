@@ -0,0 +1,21 @@
+use aoc::fuzz::{run_input, DEFAULT_CRASH_DIR};
+use std::fs;
+
+// Every input the fuzzer ever crashed on must stay fixed
+#[test]
+fn fuzz_regressions_do_not_crash() {
+    // As `aoc fuzz` does, overflows are errors rather than crashes
+    let _checked = aoc::numeric::checked_scope(true);
+    let mut paths: Vec<_> = fs::read_dir(DEFAULT_CRASH_DIR)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let day: u32 = name[3..name.find('-').unwrap()].parse().unwrap();
+        let input = fs::read(&path).unwrap();
+        assert_eq!(run_input(day, &input), Ok(()), "{}", name);
+    }
+}
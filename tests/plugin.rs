@@ -0,0 +1,37 @@
+use aoc::plugin::{load_plugins, Plugin};
+use aoc::AocRes;
+use std::env;
+use std::path::PathBuf;
+
+// Example plugins are built next to the test binaries, in target/<profile>/examples
+fn examples_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().parent().unwrap().join("examples")
+}
+
+#[test]
+fn loads_example_plugin_and_solves_sample() {
+    let plugins = load_plugins(examples_dir()).unwrap();
+    let plugin = plugins
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|p| p.day == 6)
+        .expect("day6_quadratic example plugin was not built");
+    assert_eq!(plugin.title, "Wait For It (quadratic formula)");
+    assert_eq!(
+        plugin.solve(1, "./inputs/day6_test.input").unwrap(),
+        AocRes::Int32(288)
+    );
+    assert_eq!(
+        plugin.solve(2, "./inputs/day6_test.input").unwrap(),
+        AocRes::Int32(71503)
+    );
+    assert!(plugin.solve(3, "./inputs/day6_test.input").is_err());
+    assert!(plugin.solve(1, "./inputs/missing.input").is_err());
+}
+
+#[test]
+fn rejects_files_that_are_not_plugins() {
+    assert!(Plugin::load("./inputs/day6_test.input").is_err());
+    assert!(load_plugins("./no/such/dir").is_err());
+}
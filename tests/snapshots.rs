@@ -0,0 +1,24 @@
+use aoc::snapshot::{check, diff, Status, DEFAULT_SNAPSHOT_DIR};
+use std::path::Path;
+
+// The models built from the example inputs must match the reviewed snapshots,
+// `aoc snapshots review` accepts intended changes
+#[test]
+fn snapshots_match() {
+    let outcomes = check(Path::new(DEFAULT_SNAPSHOT_DIR)).unwrap();
+    let names: Vec<&str> = outcomes.iter().map(|o| o.name.as_str()).collect();
+    for name in ["day5_test2", "day7_test", "day9_test", "day10_test"] {
+        assert!(names.contains(&name), "no snapshot of {}", name);
+    }
+    let mut failures = String::new();
+    for outcome in outcomes {
+        match outcome.status {
+            Status::Unchanged => {}
+            Status::New => failures += &format!("{}: not reviewed yet\n", outcome.name),
+            Status::Changed { expected } => {
+                failures += &format!("{}:\n{}", outcome.name, diff(&expected, &outcome.actual));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures);
+}